@@ -3,4 +3,47 @@ fn main() {
     println!("cargo:rerun-if-changed=providers/javy_quickjs_provider_v2.wasm");
     println!("cargo:rerun-if-changed=providers/javy_quickjs_provider_v3.wasm");
     println!("cargo:rerun-if-changed=providers/shopify_functions_javy_v1.wasm");
+
+    println!("cargo:rerun-if-changed=Cargo.lock");
+    println!("cargo:rustc-env=WASMTIME_VERSION={}", wasmtime_version());
+}
+
+/// The version of wasmtime the package depends on, as resolved in Cargo.lock.
+fn wasmtime_version() -> String {
+    let lockfile: toml::Table = std::fs::read_to_string("Cargo.lock")
+        .expect("Cargo.lock should be readable")
+        .parse()
+        .expect("Cargo.lock should be valid TOML");
+    let packages = lockfile
+        .get("package")
+        .and_then(toml::Value::as_array)
+        .expect("Cargo.lock should list packages");
+
+    let package_name = std::env::var("CARGO_PKG_NAME").expect("CARGO_PKG_NAME should be set");
+    let dependency = packages
+        .iter()
+        .find(|package| field(package, "name") == Some(package_name.as_str()))
+        .and_then(|package| package.get("dependencies")?.as_array())
+        .and_then(|dependencies| {
+            dependencies
+                .iter()
+                .filter_map(toml::Value::as_str)
+                .find(|dependency| *dependency == "wasmtime" || dependency.starts_with("wasmtime "))
+        })
+        .expect("Cargo.lock should list wasmtime as a dependency of the package");
+
+    // Cargo.lock only includes the version in the dependency when several versions are locked
+    match dependency.split(' ').nth(1) {
+        Some(version) => version.to_string(),
+        None => packages
+            .iter()
+            .find(|package| field(package, "name") == Some("wasmtime"))
+            .and_then(|package| field(package, "version"))
+            .expect("Cargo.lock should include the wasmtime package")
+            .to_string(),
+    }
+}
+
+fn field<'a>(package: &'a toml::Value, key: &str) -> Option<&'a str> {
+    package.get(key).and_then(toml::Value::as_str)
 }
@@ -1,14 +1,23 @@
-use crate::scale_limits_analyzer::ScaleLimitsAnalyzer;
+use crate::scale_limits_analyzer::{
+    ScaleLimits, ScaleLimitsAnalyzer, SelectedScaleLimit, SelectedScaleLimitsAnalyzer,
+};
 use anyhow::{anyhow, Result};
+use bluejay_core::{
+    definition::{prelude::*, BaseOutputTypeReference, SchemaDefinition as CoreSchemaDefinition},
+    AsIter,
+};
 use bluejay_parser::{
     ast::{
-        definition::{DefinitionDocument, SchemaDefinition},
-        executable::ExecutableDocument,
+        definition::{DefaultContext, DefinitionDocument, SchemaDefinition},
+        executable::{ExecutableDefinition, ExecutableDocument},
         Parse,
     },
-    Error,
+    error::SpanToLocation,
+    Error, HasSpan, Span,
 };
 
+type FieldsDefinition<'a> = <SchemaDefinition<'a> as CoreSchemaDefinition>::FieldsDefinition;
+
 pub struct BluejaySchemaAnalyzer;
 
 impl BluejaySchemaAnalyzer {
@@ -18,6 +27,19 @@ impl BluejaySchemaAnalyzer {
         query: &str,
         query_path: Option<&str>,
         input: &serde_json::Value,
+    ) -> Result<f64> {
+        Self::raw_scale_factor(schema_string, schema_path, query, query_path, input)
+            .map(ScaleLimits::clamp_scale_factor)
+    }
+
+    /// Computes the scale factor before it is clamped to [`ScaleLimits::MAX_SCALE_FACTOR`],
+    /// to tell how far over the maximum scaling an input is.
+    pub fn raw_scale_factor(
+        schema_string: &str,
+        schema_path: Option<&str>,
+        query: &str,
+        query_path: Option<&str>,
+        input: &serde_json::Value,
     ) -> Result<f64> {
         let document_definition = DefinitionDocument::parse(schema_string)
             .map_err(|errors| anyhow!(Error::format_errors(schema_string, schema_path, errors)))?;
@@ -41,6 +63,227 @@ impl BluejaySchemaAnalyzer {
         )
         .map_err(|e| anyhow!("Unable to analyze scale limits: {}", e.message()))
     }
+
+    /// Lists the fields annotated with `@scaleLimits` that the query selects, in selection order.
+    pub fn selected_scale_limits(
+        schema_string: &str,
+        schema_path: Option<&str>,
+        query: &str,
+        query_path: Option<&str>,
+    ) -> Result<Vec<SelectedScaleLimit>> {
+        let document_definition = DefinitionDocument::parse(schema_string)
+            .map_err(|errors| anyhow!(Error::format_errors(schema_string, schema_path, errors)))?;
+
+        let schema_definition = SchemaDefinition::try_from(&document_definition)
+            .map_err(|errors| anyhow!(Error::format_errors(schema_string, schema_path, errors)))?;
+
+        let executable_document = ExecutableDocument::parse(query)
+            .map_err(|errors| anyhow!(Error::format_errors(query, query_path, errors)))?;
+
+        let cache =
+            bluejay_validator::executable::Cache::new(&executable_document, &schema_definition);
+
+        SelectedScaleLimitsAnalyzer::analyze(
+            &executable_document,
+            &schema_definition,
+            None,
+            &Default::default(),
+            &cache,
+            (),
+        )
+        .map_err(|e| anyhow!("Unable to analyze scale limits: {}", e.message()))
+    }
+
+    /// Splits a document mixing type system definitions and operations into a schema and a query.
+    ///
+    /// bluejay parses each kind of document separately, so the document is split into runs of definitions it
+    /// parses as executable (operations and fragments) or as type system definitions, going to the query and
+    /// the schema respectively.
+    pub fn split_document(document: &str, document_path: Option<&str>) -> Result<(String, String)> {
+        let mut schema = Vec::new();
+        let mut query = Vec::new();
+        // The document with what was split so far blanked out, so offsets and error locations stay those of the
+        // whole document
+        let mut remaining = document.as_bytes().to_vec();
+        let mut start = 0;
+
+        while start < document.len() {
+            let rest = std::str::from_utf8(&remaining).expect("Blanking keeps the document UTF-8");
+            let end = match ExecutableDefinition::parse(rest) {
+                Ok(definition) => {
+                    let end = match &definition {
+                        ExecutableDefinition::Operation(operation) => operation.span(),
+                        ExecutableDefinition::Fragment(fragment) => fragment.span(),
+                    }
+                    .byte_range()
+                    .end;
+                    query.push(document[start..end].trim());
+                    end
+                }
+                Err(executable_errors) => match DefinitionDocument::<DefaultContext>::parse(rest) {
+                    Ok(_) => {
+                        schema.push(document[start..].trim());
+                        document.len()
+                    }
+                    // The type system definitions end where bluejay first fails to parse one
+                    Err(errors) => match Self::first_error_offset(rest, errors) {
+                        Some(end) if end > start => {
+                            schema.push(document[start..end].trim());
+                            end
+                        }
+                        // Only comments and whitespace are left
+                        None => break,
+                        Some(_) => {
+                            return Err(anyhow!(Error::format_errors(
+                                rest,
+                                document_path,
+                                executable_errors
+                            )))
+                        }
+                    },
+                },
+            };
+
+            for byte in &mut remaining[start..end] {
+                if !matches!(byte, b'\n' | b'\r') {
+                    *byte = b' ';
+                }
+            }
+            start = end;
+        }
+
+        Ok((schema.join("\n\n"), query.join("\n\n")))
+    }
+
+    /// Byte offset in `document` of the first of `errors` that has a location.
+    fn first_error_offset(document: &str, errors: Vec<Error>) -> Option<usize> {
+        let location = Error::into_graphql_errors(document, errors)
+            .into_iter()
+            .flat_map(|error| error.locations)
+            .next()?;
+
+        // Locations are lines and columns in characters, find the first character at or after it
+        let mut span_to_location = SpanToLocation::new(document);
+        let offsets: Vec<usize> = document.char_indices().map(|(offset, _)| offset).collect();
+        let index = offsets.partition_point(|&offset| {
+            span_to_location
+                .convert(&Span::from(offset..offset))
+                .is_some_and(|(line, col)| (line, col) < (location.line, location.col))
+        });
+        Some(offsets.get(index).copied().unwrap_or(document.len()))
+    }
+
+    /// Builds a query selecting every field annotated with `@scaleLimits` that is reachable from the query root.
+    ///
+    /// Analyzing this query gives an upper bound of the scale factor, as the Function's real query may select
+    /// only some of these fields. Fields with required arguments can't be selected without values and are skipped,
+    /// as are recursive types beyond their first occurrence on a path.
+    pub fn full_query(schema_string: &str, schema_path: Option<&str>) -> Result<String> {
+        let document_definition = DefinitionDocument::parse(schema_string)
+            .map_err(|errors| anyhow!(Error::format_errors(schema_string, schema_path, errors)))?;
+
+        let schema_definition = SchemaDefinition::try_from(&document_definition)
+            .map_err(|errors| anyhow!(Error::format_errors(schema_string, schema_path, errors)))?;
+
+        let selection_set = Self::annotated_selection_set(
+            &schema_definition,
+            schema_definition.query().fields_definition(),
+            &mut Vec::new(),
+        )
+        .unwrap_or_else(|| "__typename".to_string());
+
+        Ok(format!("{{ {selection_set} }}"))
+    }
+
+    fn annotated_selection_set<'a>(
+        schema_definition: &'a SchemaDefinition<'a>,
+        fields_definition: &'a FieldsDefinition<'a>,
+        visited_types: &mut Vec<&'a str>,
+    ) -> Option<String> {
+        let selections: Vec<String> = fields_definition
+            .iter()
+            .filter(|field_definition| !field_definition.is_builtin())
+            .filter(|field_definition| {
+                !field_definition
+                    .arguments_definition()
+                    .is_some_and(|arguments| {
+                        arguments.iter().any(|argument| argument.is_required())
+                    })
+            })
+            .filter_map(|field_definition| {
+                let annotated = ScaleLimits::rate_for_field_definition(field_definition).is_some();
+                let base = field_definition.r#type().base(schema_definition);
+
+                let nested = match base {
+                    BaseOutputTypeReference::Object(object) => Self::nested_selection_set(
+                        schema_definition,
+                        object.name(),
+                        object.fields_definition(),
+                        visited_types,
+                    ),
+                    BaseOutputTypeReference::Interface(interface) => Self::nested_selection_set(
+                        schema_definition,
+                        interface.name(),
+                        interface.fields_definition(),
+                        visited_types,
+                    ),
+                    BaseOutputTypeReference::Union(union) => {
+                        let fragments: Vec<String> = union
+                            .union_member_types()
+                            .iter()
+                            .filter_map(|member| {
+                                let object = member.member_type(schema_definition);
+                                Self::nested_selection_set(
+                                    schema_definition,
+                                    object.name(),
+                                    object.fields_definition(),
+                                    visited_types,
+                                )
+                                .map(|selection_set| {
+                                    format!("... on {} {{ {} }}", object.name(), selection_set)
+                                })
+                            })
+                            .collect();
+                        (!fragments.is_empty()).then(|| fragments.join(" "))
+                    }
+                    _ => None,
+                };
+
+                match nested {
+                    Some(selection_set) => Some(format!(
+                        "{} {{ {} }}",
+                        field_definition.name(),
+                        selection_set
+                    )),
+                    None if annotated && base.is_composite() => {
+                        Some(format!("{} {{ __typename }}", field_definition.name()))
+                    }
+                    None if annotated => Some(field_definition.name().to_string()),
+                    None => None,
+                }
+            })
+            .collect();
+
+        (!selections.is_empty()).then(|| selections.join(" "))
+    }
+
+    fn nested_selection_set<'a>(
+        schema_definition: &'a SchemaDefinition<'a>,
+        type_name: &'a str,
+        fields_definition: &'a FieldsDefinition<'a>,
+        visited_types: &mut Vec<&'a str>,
+    ) -> Option<String> {
+        if visited_types.contains(&type_name) {
+            return None;
+        }
+
+        visited_types.push(type_name);
+        let selection_set =
+            Self::annotated_selection_set(schema_definition, fields_definition, visited_types);
+        visited_types.pop();
+
+        selection_set
+    }
 }
 
 #[cfg(test)]
@@ -278,4 +521,211 @@ mod tests {
             "The scale factor did not match the expected value, indicating potential double counting"
         );
     }
+
+    #[test]
+    fn test_multiple_scale_limits_directives_use_max_rate() {
+        let schema_string = r#"
+            directive @scaleLimits(rate: Float!) repeatable on FIELD_DEFINITION
+            type Query {
+                cartLines: [String] @scaleLimits(rate: 0.001) @scaleLimits(rate: 0.005)
+            }
+        "#;
+        let query = "{ cartLines }";
+        let input_json = json!({
+            "cartLines": vec!["moeowomeow"; 500]
+        });
+
+        let result = BluejaySchemaAnalyzer::analyze_schema_definition(
+            schema_string,
+            Some("schema.graphql"),
+            query,
+            Some("query.graphql"),
+            &input_json,
+        );
+        assert!(
+            result.is_ok(),
+            "Expected successful analysis but got an error: {:?}",
+            result
+        );
+
+        let scale_factor = result.unwrap();
+        let expected_scale_factor = 2.5;
+        assert_eq!(
+            scale_factor, expected_scale_factor,
+            "The scale factor did not use the highest rate across scaleLimits directives"
+        );
+    }
+
+    #[test]
+    fn test_full_query() {
+        let schema_string = r#"
+            directive @scaleLimits(rate: Float!) on FIELD_DEFINITION
+            type Query {
+                cart: Cart
+                shop: Shop
+                metafield(key: String!): String @scaleLimits(rate: 0.005)
+            }
+
+            type Cart {
+                lines: [CartLine!]! @scaleLimits(rate: 0.005)
+                note: String
+                attribute: String @scaleLimits(rate: 0.001)
+            }
+
+            type CartLine {
+                quantity: Int!
+                cart: Cart
+            }
+
+            type Shop {
+                name: String
+            }
+        "#;
+
+        let query =
+            BluejaySchemaAnalyzer::full_query(schema_string, Some("schema.graphql")).unwrap();
+
+        assert_eq!(
+            query, "{ cart { lines { __typename } attribute } }",
+            "The full query should select only reachable annotated fields"
+        );
+    }
+
+    #[test]
+    fn test_raw_scale_factor_is_not_clamped() {
+        let schema_string = r#"
+            directive @scaleLimits(rate: Float!) on FIELD_DEFINITION
+            type Query {
+                cartLines: [String] @scaleLimits(rate: 0.5)
+            }
+        "#;
+        let query = "{ cartLines }";
+        let input_json = json!({
+            "cartLines": vec!["item"; 40]
+        });
+
+        let raw_scale_factor = BluejaySchemaAnalyzer::raw_scale_factor(
+            schema_string,
+            Some("schema.graphql"),
+            query,
+            Some("query.graphql"),
+            &input_json,
+        )
+        .unwrap();
+        let scale_factor = BluejaySchemaAnalyzer::analyze_schema_definition(
+            schema_string,
+            Some("schema.graphql"),
+            query,
+            Some("query.graphql"),
+            &input_json,
+        )
+        .unwrap();
+
+        assert_eq!(raw_scale_factor, 20.0);
+        assert_eq!(scale_factor, 10.0);
+    }
+
+    #[test]
+    fn test_split_document() {
+        let document = r#"
+            directive @scaleLimits(rate: Float!) on FIELD_DEFINITION
+
+            """
+            The { root } type
+            """
+            type Query {
+                cartLines(first: Int = 1): [String] @scaleLimits(rate: 0.005)
+            }
+
+            # A comment with a query { keyword }
+            query Input($first: Int) @dir(arg: 1) {
+                cartLines(first: $first)
+            }
+
+            { cartLines }
+
+            scalar Decimal
+
+            fragment Lines on Query { cartLines }
+            # A trailing comment
+        "#;
+
+        let (schema, query) = BluejaySchemaAnalyzer::split_document(document, None).unwrap();
+
+        assert!(schema.starts_with("directive @scaleLimits"));
+        assert!(schema.contains("The { root } type"));
+        assert!(schema.ends_with("scalar Decimal"));
+        assert!(!schema.contains("query Input"));
+        assert_eq!(
+            query,
+            "query Input($first: Int) @dir(arg: 1) {\n                cartLines(first: $first)\n            }\n\n{ cartLines }\n\nfragment Lines on Query { cartLines }"
+        );
+
+        let error = BluejaySchemaAnalyzer::split_document("type Query { a: Int }\nquery {", None)
+            .unwrap_err();
+        assert!(error.to_string().contains("Unexpected EOF"));
+    }
+
+    #[test]
+    fn test_selected_scale_limits() {
+        let schema_string = r#"
+            directive @scaleLimits(rate: Float!) on FIELD_DEFINITION
+            type Query {
+                cart: Cart
+            }
+
+            type Cart {
+                lines: [CartLine!]! @scaleLimits(rate: 0.005)
+                note: String
+                attribute: String @scaleLimits(rate: 0.001)
+            }
+
+            type CartLine {
+                quantity: Int!
+            }
+        "#;
+        let query = "{ cart { note items: lines { quantity } } }";
+
+        let selected = BluejaySchemaAnalyzer::selected_scale_limits(
+            schema_string,
+            Some("schema.graphql"),
+            query,
+            Some("query.graphql"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            selected,
+            vec![SelectedScaleLimit {
+                path: "cart.items".to_string(),
+                rate: 0.005,
+            }],
+            "Only the selected annotated fields should be listed"
+        );
+    }
+
+    #[test]
+    fn test_full_query_scale_factor() {
+        let schema_string = r#"
+            directive @scaleLimits(rate: Float!) on FIELD_DEFINITION
+            type Query {
+                cartLines: [String] @scaleLimits(rate: 0.005)
+            }
+        "#;
+        let input_json = json!({
+            "cartLines": vec!["moeowomeow"; 500]
+        });
+
+        let query = BluejaySchemaAnalyzer::full_query(schema_string, None).unwrap();
+        let scale_factor = BluejaySchemaAnalyzer::analyze_schema_definition(
+            schema_string,
+            None,
+            &query,
+            None,
+            &input_json,
+        )
+        .unwrap();
+
+        assert_eq!(scale_factor, 2.5);
+    }
 }
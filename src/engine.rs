@@ -1,15 +1,32 @@
 use anyhow::{anyhow, Result};
 use rust_embed::RustEmbed;
-use std::{collections::HashSet, io::Cursor, path::PathBuf};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt,
+    io::Cursor,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, RecvTimeoutError},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
 use wasi_common::{I32Exit, WasiCtx};
-use wasmtime::{AsContextMut, Config, Engine, Linker, Module, ResourceLimiter, Store};
+use wasmtime::{
+    AsContextMut, Config, Engine, Extern, ExternType, Linker, Module, ResourceLimiter, Store, Trap,
+    Val,
+};
 
 use crate::{
     function_run_result::{
         FunctionOutput::{self, InvalidJsonOutput, JsonOutput},
-        FunctionRunResult, InvalidOutput,
+        FunctionRunResult, InputCodec, InvalidOutput,
     },
-    logs::LogStream,
+    logs::{LogSink, LogStream},
+    preopen::Preopen,
+    run_error::RunError,
 };
 
 #[derive(Clone)]
@@ -22,6 +39,18 @@ pub struct ProfileOpts {
 #[folder = "providers/"]
 struct StandardProviders;
 
+/// Version of wasmtime the runner is built against.
+pub const WASMTIME_VERSION: &str = env!("WASMTIME_VERSION");
+
+/// Names of the providers embedded in the runner, sorted alphabetically.
+pub fn standard_providers() -> Vec<String> {
+    let mut providers: Vec<String> = StandardProviders::iter()
+        .map(|file_name| file_name.trim_end_matches(".wasm").to_string())
+        .collect();
+    providers.sort();
+    providers
+}
+
 fn import_modules<T>(
     module: &Module,
     engine: &Engine,
@@ -47,16 +76,123 @@ fn import_modules<T>(
     });
 }
 
+/// Formats an error with its causes, as wasmtime wraps traps and host errors in a wasm backtrace context.
+fn error_report(error: &anyhow::Error) -> String {
+    let mut report = error.to_string();
+    for cause in error.chain().skip(1) {
+        report.push_str(&format!("\n\nCaused by:\n    {cause}"));
+    }
+    report
+}
+
+/// Links every imported function not provided by WASI or a standard provider to a stub that traps when called,
+/// so partially-built Functions can be instantiated.
+fn stub_unknown_imports_as_traps<T>(
+    module: &Module,
+    linker: &mut Linker<T>,
+    mut store: &mut Store<T>,
+) -> Result<()> {
+    for import in module.imports() {
+        let ExternType::Func(func_type) = import.ty() else {
+            continue;
+        };
+        if linker
+            .get(&mut store, import.module(), import.name())
+            .is_some()
+        {
+            continue;
+        }
+
+        let message = format!(
+            "called unimplemented import {}.{}",
+            import.module(),
+            import.name()
+        );
+        linker.func_new(
+            import.module(),
+            import.name(),
+            func_type,
+            move |_caller, _params, _results| Err(anyhow!(message.clone())),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Interrupts the Function at its next epoch check when it logs, forwarding the log to the wrapped sink.
+struct InterruptOnLog {
+    engine: Engine,
+    sink: Option<Arc<dyn LogSink>>,
+}
+
+impl LogSink for InterruptOnLog {
+    fn write_log(&self, log: &str) {
+        self.engine.increment_epoch();
+        if let Some(sink) = &self.sink {
+            sink.write_log(log);
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct FunctionRunParams<'a> {
     pub function_path: PathBuf,
     pub input: Vec<u8>,
+    /// How `input` is encoded, used to echo it in the run result.
+    pub input_codec: InputCodec,
+    /// The already decoded `input`, to echo it without decoding it again.
+    pub input_json: Option<serde_json::Value>,
     pub export: &'a str,
+    pub export_index: Option<usize>,
     pub profile_opts: Option<&'a ProfileOpts>,
     pub scale_factor: f64,
+    pub forbid_wasi: bool,
+    pub dump_memory_on_trap: bool,
+    pub preallocate_memory_pages: Option<u64>,
+    pub max_wasm_stack: Option<usize>,
+    /// Receives the Function's own logs as it writes them, without the runtime error appended to the run result's.
+    pub log_sink: Option<Arc<dyn LogSink>>,
+    pub stub_unknown_imports: bool,
+    pub trace_imports: bool,
+    pub max_instructions: Option<u64>,
+    /// Add a warning to the run result when the Function executes more instructions than this.
+    pub max_instructions_soft: Option<u64>,
+    /// Keep at most this many of the Function's own log entries, the runtime error not counting as one.
+    pub max_log_entries: Option<usize>,
+    /// Interrupt the Function once it writes to stderr. Relies on epoch interruption, so it can't be combined
+    /// with profiling.
+    pub abort_on_first_log: bool,
+    pub preopens: &'a [Preopen],
+    /// Stop the Function once it executed this many instructions, which isn't considered a failure.
+    pub run_until_instructions: Option<u64>,
+    /// Interrupt the Function once it ran for this long. A watchdog thread is spawned for the run when set.
+    /// Relies on epoch interruption, so it can't be combined with profiling.
+    pub timeout: Option<Duration>,
+}
+
+/// Marks the error of a Function interrupted by its timeout.
+#[derive(Debug)]
+struct TimedOut(Duration);
+
+impl fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The Function exceeded the timeout of {}ms",
+            self.0.as_millis()
+        )
+    }
 }
 
 const STARTING_FUEL: u64 = u64::MAX;
+// Bounds the memory used by chatty Functions, well above the production log limit.
+const MAX_LOG_BYTES: usize = 1024 * 1024;
+const MAX_MEMORY_DUMP_BYTES: usize = 256 * 1024 * 1024;
+const DEFAULT_MAX_WASM_STACK: usize = 512 * 1024; // wasmtime's default
+const WASM_PAGE_SIZE: usize = 64 * 1024;
+// Separates the Function's own logs from the error the runner appends to them.
+const RUNTIME_ERROR_SEPARATOR: &str = "--- runtime error ---\n";
+const WASI_MODULES: [&str; 2] = ["wasi_snapshot_preview1", "wasi_unstable"];
 const MAXIMUM_MEMORIES: usize = 2; // 1 for the module, 1 for Javy's provider
 
 struct FunctionContext {
@@ -80,17 +216,28 @@ impl FunctionContext {
 #[derive(Default)]
 pub struct MemoryLimiter {
     max_memory_bytes: usize,
+    /// Every memory growth as a `(current, desired)` size in bytes, in order.
+    growths: Vec<(usize, usize)>,
+    /// The `(from, to)` size in bytes of the memory the runner grew before invoking the Function. Growths of a
+    /// memory at least that large are shifted back by the preallocation, so that only the Function's own usage
+    /// is recorded.
+    preallocation: Option<(usize, usize)>,
 }
 
 impl ResourceLimiter for MemoryLimiter {
     /// See [`wasmtime::ResourceLimiter::memory_growing`].
     fn memory_growing(
         &mut self,
-        _current: usize,
+        current: usize,
         desired: usize,
         _maximum: Option<usize>,
     ) -> anyhow::Result<bool> {
+        let (current, desired) = match self.preallocation {
+            Some((from, to)) if current >= to => (current - (to - from), desired - (to - from)),
+            _ => (current, desired),
+        };
         self.max_memory_bytes = std::cmp::max(self.max_memory_bytes, desired);
+        self.growths.push((current, desired));
         Ok(true)
     }
 
@@ -109,55 +256,397 @@ impl ResourceLimiter for MemoryLimiter {
     }
 }
 
+/// Resolves the name of the export at `index` in the module's export list, which must be a function.
+fn export_name_at_index(module: &Module, index: usize) -> Result<String> {
+    let export = module.exports().nth(index).ok_or_else(|| {
+        anyhow!(
+            "Export index {} is out of range, the module has {} exports",
+            index,
+            module.exports().len()
+        )
+    })?;
+
+    match export.ty() {
+        ExternType::Func(_) => Ok(export.name().to_string()),
+        _ => Err(anyhow!(
+            "Export index {} (`{}`) is not a function export",
+            index,
+            export.name()
+        )),
+    }
+}
+
+fn new_engine(max_wasm_stack: usize) -> Result<Engine> {
+    Engine::new(
+        Config::new()
+            .wasm_multi_memory(true)
+            .wasm_threads(false)
+            .consume_fuel(true)
+            .epoch_interruption(true)
+            .max_wasm_stack(max_wasm_stack),
+    )
+}
+
+/// Compiles the module `iterations` times with the configuration used to run it, without instantiating it,
+/// and returns how long each compilation took. The module is read once, so only compilation is measured.
+pub fn measure_compile(function_path: &Path, iterations: usize) -> Result<Vec<Duration>> {
+    let engine = new_engine(DEFAULT_MAX_WASM_STACK)?;
+    let module_bytes = std::fs::read(function_path).map_err(|e| {
+        anyhow!(
+            "Couldn't load the Function {:?}: failed to read from `{}`: {}",
+            function_path,
+            function_path.display(),
+            e
+        )
+    })?;
+
+    (0..iterations)
+        .map(|_| {
+            let compile_start = Instant::now();
+            Module::new(&engine, &module_bytes)
+                .map_err(|e| anyhow!("Couldn't load the Function {:?}: {}", function_path, e))?;
+            Ok(compile_start.elapsed())
+        })
+        .collect()
+}
+
+/// Lists the module's function exports with whether each can be invoked by the runner, i.e. is `() -> ()`.
+pub fn function_exports(function_path: &Path) -> Result<Vec<(String, Result<()>)>> {
+    let engine = Engine::default();
+    let module = Module::from_file(&engine, function_path)
+        .map_err(|e| anyhow!("Couldn't load the Function {:?}: {}", function_path, e))?;
+
+    Ok(module
+        .exports()
+        .filter(|export| matches!(export.ty(), ExternType::Func(_)))
+        .map(|export| {
+            let name = export.name().to_string();
+            let signature = check_export_signature(&module, &name);
+            (name, signature)
+        })
+        .collect())
+}
+
+/// Wraps every function the module imports from a provider with a shim printing the call and its arguments to
+/// stderr. WASI functions are implemented by the host and read the calling instance's memory, which a shim can't
+/// forward, so they aren't traced.
+fn trace_import_calls<T: 'static>(
+    module: &Module,
+    linker: &mut Linker<T>,
+    mut store: &mut Store<T>,
+) -> Result<()> {
+    linker.allow_shadowing(true);
+    for import in module.imports() {
+        let ExternType::Func(func_type) = import.ty() else {
+            continue;
+        };
+        if WASI_MODULES.contains(&import.module()) {
+            continue;
+        }
+        let Some(Extern::Func(func)) = linker.get(&mut store, import.module(), import.name())
+        else {
+            continue;
+        };
+
+        let name = format!("{}.{}", import.module(), import.name());
+        linker.func_new(
+            import.module(),
+            import.name(),
+            func_type,
+            move |mut caller, params, results| {
+                let args: Vec<String> = params.iter().map(format_val).collect();
+                eprintln!("{}({})", name, args.join(", "));
+                func.call(&mut caller, params, results)
+            },
+        )?;
+    }
+    linker.allow_shadowing(false);
+
+    Ok(())
+}
+
+fn format_val(val: &Val) -> String {
+    match val {
+        Val::I32(value) => value.to_string(),
+        Val::I64(value) => value.to_string(),
+        Val::F32(bits) => f32::from_bits(*bits).to_string(),
+        Val::F64(bits) => f64::from_bits(*bits).to_string(),
+        other => format!("{other:?}"),
+    }
+}
+
+/// The name the Function is reported under: its file name, or `function` if the path doesn't end in one.
+fn function_name(function_path: &Path) -> &str {
+    function_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("function")
+}
+
+/// Checks that the export is a `() -> ()` function, as the runner invokes it without arguments.
+fn check_export_signature(module: &Module, export: &str) -> Result<()> {
+    let Some(ExternType::Func(func_type)) = module.get_export(export) else {
+        return Ok(());
+    };
+
+    if func_type.params().len() == 0 && func_type.results().len() == 0 {
+        return Ok(());
+    }
+
+    let params: Vec<String> = func_type.params().map(|ty| ty.to_string()).collect();
+    let results: Vec<String> = func_type.results().map(|ty| ty.to_string()).collect();
+    let results = match results.as_slice() {
+        [result] => result.clone(),
+        _ => format!("({})", results.join(", ")),
+    };
+
+    Err(anyhow!(
+        "Export `{}` has signature ({}) -> {}, expected () -> ()",
+        export,
+        params.join(", "),
+        results
+    ))
+}
+
 pub fn run(params: FunctionRunParams) -> Result<FunctionRunResult> {
+    execute(params)
+        .map(|(function_run_result, _)| function_run_result)
+        .map_err(|error| match error {
+            RunError::Setup(error) | RunError::Instantiation(error) => error,
+            error => anyhow!(error),
+        })
+}
+
+/// Like [`run`], but fails with a [`RunError`] describing what went wrong, including when the Function itself
+/// fails or produces invalid output.
+pub fn try_run(params: FunctionRunParams) -> Result<FunctionRunResult, RunError> {
+    let (function_run_result, module_result) = execute(params)?;
+
+    let result = Box::new(function_run_result);
+    if let Err(error) = module_result {
+        if error.downcast_ref::<TimedOut>().is_some() {
+            return Err(RunError::Timeout { result });
+        }
+        return Err(match error.downcast_ref::<Trap>() {
+            Some(Trap::OutOfFuel) => RunError::OutOfFuel { result },
+            _ => RunError::Trap {
+                message: error_report(&error),
+                result,
+            },
+        });
+    }
+
+    match result.output {
+        InvalidJsonOutput(ref invalid_output) => Err(RunError::InvalidOutput {
+            message: invalid_output.error.clone(),
+            result,
+        }),
+        JsonOutput(_) => Ok(*result),
+    }
+}
+
+/// The Function's logs followed by the runtime error, if any. The error isn't part of the log stream, so the log
+/// budget never truncates it.
+fn logs_with_runtime_error(logs: &LogStream, error_logs: &str) -> String {
+    let mut logs = logs.to_string();
+    if !error_logs.is_empty() {
+        if !logs.is_empty() && !logs.ends_with('\n') {
+            logs.push('\n');
+        }
+        logs.push_str(RUNTIME_ERROR_SEPARATOR);
+        logs.push_str(error_logs);
+    }
+    logs
+}
+
+/// Runs the Function, returning the run result along with how the Function's own execution ended.
+fn execute(params: FunctionRunParams) -> Result<(FunctionRunResult, Result<()>), RunError> {
     let FunctionRunParams {
         function_path,
         input,
+        input_codec,
+        input_json,
         export,
+        export_index,
         profile_opts,
         scale_factor,
+        forbid_wasi,
+        dump_memory_on_trap,
+        preallocate_memory_pages,
+        max_wasm_stack,
+        log_sink,
+        stub_unknown_imports,
+        trace_imports,
+        max_instructions,
+        max_instructions_soft,
+        max_log_entries,
+        abort_on_first_log,
+        preopens,
+        run_until_instructions,
+        timeout,
     } = params;
 
-    let engine = Engine::new(
-        Config::new()
-            .wasm_multi_memory(true)
-            .wasm_threads(false)
-            .consume_fuel(true)
-            .epoch_interruption(true),
-    )?;
-    let module = Module::from_file(&engine, &function_path)
-        .map_err(|e| anyhow!("Couldn't load the Function {:?}: {}", &function_path, e))?;
+    let starting_fuel = run_until_instructions
+        .or(max_instructions)
+        .unwrap_or(STARTING_FUEL);
+    let mut warnings = Vec::new();
+
+    let max_wasm_stack = max_wasm_stack.unwrap_or(DEFAULT_MAX_WASM_STACK);
+
+    let engine = new_engine(max_wasm_stack).map_err(RunError::Setup)?;
+    let module_bytes = std::fs::read(&function_path).map_err(|e| {
+        RunError::ModuleLoad(format!(
+            "Couldn't load the Function {:?}: failed to read from `{}`: {}",
+            &function_path,
+            function_path.display(),
+            e
+        ))
+    })?;
+    let module_hash = format!("{:x}", Sha256::digest(&module_bytes));
+    let compile_start = Instant::now();
+    let module = Module::new(&engine, &module_bytes).map_err(|e| {
+        RunError::ModuleLoad(format!(
+            "Couldn't load the Function {:?}: {}",
+            &function_path, e
+        ))
+    })?;
+    let compile_us = compile_start.elapsed().as_micros() as u64;
+
+    if forbid_wasi {
+        if let Some(import) = module
+            .imports()
+            .find(|import| WASI_MODULES.contains(&import.module()))
+        {
+            return Err(RunError::ForbiddenImport(format!(
+                "The Function imports WASI (`{}::{}`), which is not allowed",
+                import.module(),
+                import.name()
+            )));
+        }
+    }
 
-    let input_stream = wasi_common::pipe::ReadPipe::new(Cursor::new(input.clone()));
+    let export = match export_index {
+        Some(index) => export_name_at_index(&module, index)
+            .map_err(|e| RunError::InvalidExport(e.to_string()))?,
+        None => export.to_string(),
+    };
+    check_export_signature(&module, &export).map_err(|e| RunError::InvalidExport(e.to_string()))?;
+
+    let input_json = match input_json {
+        Some(input_json) => input_json,
+        None => input_codec
+            .decode(&input)
+            .map_err(|e| RunError::InvalidInput(e.to_string()))?,
+    };
+
+    let input_stream = wasi_common::pipe::ReadPipe::new(Cursor::new(input));
     let output_stream = wasi_common::pipe::WritePipe::new_in_memory();
-    let error_stream = wasi_common::pipe::WritePipe::new(LogStream::default());
+    let mut log_stream = LogStream::with_max_bytesize(MAX_LOG_BYTES);
+    let log_sink = if abort_on_first_log {
+        Some(Arc::new(InterruptOnLog {
+            engine: engine.clone(),
+            sink: log_sink,
+        }) as Arc<dyn LogSink>)
+    } else {
+        log_sink
+    };
+    if let Some(log_sink) = log_sink {
+        log_stream = log_stream.with_sink(log_sink);
+    }
+    if let Some(max_log_entries) = max_log_entries {
+        log_stream = log_stream.with_max_entries(max_log_entries);
+    }
+    let error_stream = wasi_common::pipe::WritePipe::new(log_stream);
 
     let memory_usage: u64;
+    let memory_growths: Vec<(u64, u64)>;
     let instructions: u64;
     let mut error_logs: String = String::new();
     let mut module_result: Result<(), anyhow::Error>;
     let profile_data: Option<String>;
+    let mut memory_dump: Option<Vec<u8>> = None;
+    let runtime_us: u64;
+    let exit_code: Option<i32>;
 
     {
         let mut linker = Linker::new(&engine);
-        wasi_common::sync::add_to_linker(&mut linker, |ctx: &mut FunctionContext| &mut ctx.wasi)?;
+        wasi_common::sync::add_to_linker(&mut linker, |ctx: &mut FunctionContext| &mut ctx.wasi)
+            .map_err(RunError::Setup)?;
         let wasi = deterministic_wasi_ctx::build_wasi_ctx();
         wasi.set_stdin(Box::new(input_stream));
         wasi.set_stdout(Box::new(output_stream.clone()));
         wasi.set_stderr(Box::new(error_stream.clone()));
+        for preopen in preopens {
+            wasi.push_preopened_dir(
+                preopen.open().map_err(RunError::Setup)?,
+                &preopen.guest_path,
+            )
+            .map_err(|e| RunError::Setup(e.into()))?;
+        }
         let function_context = FunctionContext::new(wasi);
         let mut store = Store::new(&engine, function_context);
         store.limiter(|s| &mut s.limiter);
-        store.set_fuel(STARTING_FUEL)?;
+        store.set_fuel(starting_fuel).map_err(RunError::Setup)?;
         store.set_epoch_deadline(1);
 
         import_modules(&module, &engine, &mut linker, &mut store);
 
-        linker.module(&mut store, "Function", &module)?;
-        let instance = linker.instantiate(&mut store, &module)?;
+        if stub_unknown_imports {
+            stub_unknown_imports_as_traps(&module, &mut linker, &mut store)
+                .map_err(RunError::Instantiation)?;
+        }
+
+        if trace_imports {
+            trace_import_calls(&module, &mut linker, &mut store)
+                .map_err(RunError::Instantiation)?;
+        }
+
+        linker
+            .module(&mut store, "Function", &module)
+            .map_err(RunError::Instantiation)?;
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .map_err(RunError::Instantiation)?;
 
-        let func = instance.get_typed_func::<(), ()>(store.as_context_mut(), export)?;
+        if let Some(pages) = preallocate_memory_pages {
+            if let Some(memory) = instance.get_memory(&mut store, "memory") {
+                // Growing up front would otherwise be reported as the Function's own memory usage.
+                let max_memory_bytes = store.data().max_memory_bytes();
+                let current_pages = memory.size(&store);
+                if pages > current_pages {
+                    memory
+                        .grow(&mut store, pages - current_pages)
+                        .map_err(RunError::Setup)?;
+                    let limiter = &mut store.data_mut().limiter;
+                    limiter.growths.pop();
+                    limiter.preallocation = Some((
+                        current_pages as usize * WASM_PAGE_SIZE,
+                        pages as usize * WASM_PAGE_SIZE,
+                    ));
+                }
+                store.data_mut().limiter.max_memory_bytes = max_memory_bytes;
+            }
+        }
 
+        let func = instance
+            .get_typed_func::<(), ()>(store.as_context_mut(), &export)
+            .map_err(|e| RunError::InvalidExport(e.to_string()))?;
+
+        // Interrupts the Function at its next epoch check unless it completes within the timeout
+        let timed_out = Arc::new(AtomicBool::new(false));
+        let (completed, completion) = mpsc::channel::<()>();
+        let watchdog = timeout.map(|timeout| {
+            let engine = engine.clone();
+            let timed_out = timed_out.clone();
+            std::thread::spawn(move || {
+                if completion.recv_timeout(timeout) == Err(RecvTimeoutError::Timeout) {
+                    timed_out.store(true, Ordering::SeqCst);
+                    engine.increment_epoch();
+                }
+            })
+        });
+
+        let start = Instant::now();
         (module_result, profile_data) = if let Some(profile_opts) = profile_opts {
             let (result, profile_data) = wasmprof::ProfilerBuilder::new(&mut store)
                 .frequency(profile_opts.interval)
@@ -171,72 +660,177 @@ pub fn run(params: FunctionRunParams) -> Result<FunctionRunResult> {
         } else {
             (func.call(store.as_context_mut(), ()), None)
         };
+        runtime_us = start.elapsed().as_micros() as u64;
+
+        drop(completed);
+        if let Some(watchdog) = watchdog {
+            watchdog.join().expect("The timeout thread panicked");
+        }
 
         // modules may exit with a specific exit code, an exit code of 0 is considered success but is reported as
         // a GuestFault by wasmtime, so we need to map it to a success result. Any other exit code is considered
         // a failure.
+        exit_code = module_result.as_ref().err().and_then(|error| {
+            error
+                .downcast_ref::<wasi_common::I32Exit>()
+                .map(|I32Exit(code)| *code)
+        });
         module_result =
             module_result.or_else(|error| match error.downcast_ref::<wasi_common::I32Exit>() {
                 Some(I32Exit(0)) => Ok(()),
                 Some(I32Exit(code)) => Err(anyhow!("module exited with code: {}", code)),
                 None => Err(error),
             });
+        if let (Some(timeout), true) = (timeout, timed_out.load(Ordering::SeqCst)) {
+            module_result = module_result.map_err(|error| match error.downcast_ref::<Trap>() {
+                Some(Trap::Interrupt) => error.context(TimedOut(timeout)),
+                _ => error,
+            });
+        }
+
+        if dump_memory_on_trap {
+            let trapped =
+                matches!(&module_result, Err(error) if error.downcast_ref::<Trap>().is_some());
+            if let (true, Some(memory)) = (trapped, instance.get_memory(&mut store, "memory")) {
+                let data = memory.data(&store);
+                memory_dump = Some(data[..data.len().min(MAX_MEMORY_DUMP_BYTES)].to_vec());
+            }
+        }
 
         memory_usage = store.data().max_memory_bytes() as u64 / 1024;
-        instructions = STARTING_FUEL.saturating_sub(store.get_fuel().unwrap_or_default());
+        memory_growths = store
+            .data()
+            .limiter
+            .growths
+            .iter()
+            .map(|&(current, desired)| {
+                (
+                    (current / WASM_PAGE_SIZE) as u64,
+                    (desired / WASM_PAGE_SIZE) as u64,
+                )
+            })
+            .collect();
+        instructions = starting_fuel.saturating_sub(store.get_fuel().unwrap_or_default());
+
+        if let Some(max_instructions_soft) = max_instructions_soft {
+            if instructions > max_instructions_soft {
+                warnings.push(format!(
+                    "Instructions exceeded the soft limit of {max_instructions_soft}"
+                ));
+            }
+        }
+
+        if let (Some(run_until_instructions), Err(error)) = (run_until_instructions, &module_result)
+        {
+            if matches!(error.downcast_ref::<Trap>(), Some(Trap::OutOfFuel)) {
+                warnings.push(format!(
+                    "The Function was stopped at {run_until_instructions} instructions"
+                ));
+                module_result = Ok(());
+            }
+        }
 
         match module_result {
             Ok(_) => {}
+            Err(ref e) if matches!(e.downcast_ref::<Trap>(), Some(Trap::StackOverflow)) => {
+                error_logs = format!(
+                    "{}\nThe Function exceeded the maximum wasm stack size of {} bytes. \
+                     Reduce its stack usage, e.g. by moving large locals to the heap, \
+                     or raise the limit with --max-wasm-stack.",
+                    error_report(e),
+                    max_wasm_stack
+                );
+            }
+            Err(ref e) if matches!(e.downcast_ref::<Trap>(), Some(Trap::OutOfFuel)) => {
+                error_logs = format!(
+                    "{}\nThe Function exceeded the hard limit of {} instructions.",
+                    error_report(e),
+                    starting_fuel
+                );
+            }
+            Err(ref e) if e.downcast_ref::<TimedOut>().is_some() => {
+                error_logs = format!("{}, at {} instructions.", e, instructions);
+            }
+            Err(ref e)
+                if abort_on_first_log
+                    && matches!(e.downcast_ref::<Trap>(), Some(Trap::Interrupt)) =>
+            {
+                error_logs = format!(
+                    "The Function was aborted after its first log, at {} instructions.",
+                    instructions
+                );
+            }
             Err(ref e) => {
-                error_logs = e.to_string();
+                error_logs = error_report(e);
             }
         }
     };
 
-    let mut logs = error_stream
-        .try_into_inner()
-        .expect("Log stream reference still exists");
-
-    logs.append(error_logs.as_bytes());
+    let logs = logs_with_runtime_error(
+        &error_stream
+            .try_into_inner()
+            .expect("Log stream reference still exists"),
+        &error_logs,
+    );
 
     let raw_output = output_stream
         .try_into_inner()
         .expect("Output stream reference still exists")
         .into_inner();
 
-    let output: FunctionOutput = match serde_json::from_slice(&raw_output) {
-        Ok(json_output) => JsonOutput(json_output),
-        Err(error) => InvalidJsonOutput(InvalidOutput {
-            stdout: std::str::from_utf8(&raw_output)
-                .map_err(|e| anyhow!("Couldn't print Function Output: {}", e))
-                .unwrap()
-                .to_owned(),
-            error: error.to_string(),
-        }),
+    let output: FunctionOutput = if raw_output.is_empty() {
+        InvalidJsonOutput(InvalidOutput {
+            stdout: String::new(),
+            error: "Function produced no output".to_string(),
+        })
+    } else {
+        match serde_json::from_slice(&raw_output) {
+            Ok(json_output) => JsonOutput(json_output),
+            Err(error) => InvalidJsonOutput(InvalidOutput {
+                stdout: std::str::from_utf8(&raw_output)
+                    .map_err(|e| anyhow!("Couldn't print Function Output: {}", e))
+                    .unwrap()
+                    .to_owned(),
+                error: error.to_string(),
+            }),
+        }
     };
 
-    let name = function_path.file_name().unwrap().to_str().unwrap();
-    let size = function_path.metadata()?.len() / 1024;
-
-    let parsed_input =
-        String::from_utf8(input).map_err(|e| anyhow!("Couldn't parse input: {}", e))?;
-
-    let function_run_input = serde_json::from_str(&parsed_input)?;
+    let name = function_name(&function_path);
+    // Without a backing file, e.g. when the module was read from stdin, the compiled module's size stands in.
+    let size = match function_path.metadata() {
+        Ok(metadata) => metadata.len(),
+        Err(_) => module.serialize().map_or(0, |bytes| bytes.len() as u64),
+    } / 1024;
 
     let function_run_result = FunctionRunResult {
         name: name.to_string(),
         size,
+        module_hash,
         memory_usage,
         instructions,
-        logs: logs.to_string(),
-        input: function_run_input,
+        max_instructions_soft,
+        max_instructions_hard: max_instructions,
+        runtime_us,
+        compile_us,
+        logs,
+        input: input_json,
         output,
+        output_hash: None,
+        warnings,
+        deterministic: None,
+        metrics: BTreeMap::new(),
+        memory_growth_count: memory_growths.len() as u64,
+        memory_growths,
+        exit_code,
+        input_codec: Some(input_codec),
         profile: profile_data,
+        memory_dump,
         scale_factor,
         success: module_result.is_ok(),
     };
 
-    Ok(function_run_result)
+    Ok((function_run_result, module_result))
 }
 
 #[cfg(test)]
@@ -245,7 +839,7 @@ mod tests {
     use serde_json::json;
 
     use super::*;
-    use std::path::Path;
+    use std::{error::Error as _, path::Path};
 
     const DEFAULT_EXPORT: &str = "_start";
 
@@ -317,6 +911,7 @@ mod tests {
         .unwrap();
 
         assert_eq!(function_run_result.logs, "");
+        assert_eq!(function_run_result.exit_code, Some(0));
     }
 
     #[test]
@@ -329,7 +924,121 @@ mod tests {
         })
         .unwrap();
 
-        assert_eq!(function_run_result.logs, "module exited with code: 1");
+        assert_eq!(
+            function_run_result.logs,
+            "--- runtime error ---\nmodule exited with code: 1"
+        );
+        assert_eq!(function_run_result.exit_code, Some(1));
+    }
+
+    #[test]
+    fn test_no_output() {
+        let function_run_result = run(FunctionRunParams {
+            function_path: Path::new("tests/fixtures/build/no_output.wasm").to_path_buf(),
+            input: "{}".as_bytes().to_vec(),
+            export: DEFAULT_EXPORT,
+            ..Default::default()
+        })
+        .unwrap();
+
+        match function_run_result.output {
+            FunctionOutput::InvalidJsonOutput(invalid_output) => {
+                assert_eq!(invalid_output.error, "Function produced no output");
+                assert_eq!(invalid_output.stdout, "");
+            }
+            FunctionOutput::JsonOutput(_) => panic!("Expected no output to be reported as invalid"),
+        }
+    }
+
+    #[test]
+    fn test_dump_memory_on_trap() {
+        let function_run_result = run(FunctionRunParams {
+            function_path: Path::new("tests/fixtures/build/trap.wasm").to_path_buf(),
+            input: "{}".as_bytes().to_vec(),
+            export: DEFAULT_EXPORT,
+            dump_memory_on_trap: true,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let memory_dump = function_run_result.memory_dump.unwrap();
+        assert_eq!(memory_dump.len(), 64 * 1024);
+        assert!(memory_dump.starts_with(b"trap"));
+    }
+
+    #[test]
+    fn test_stack_overflow() {
+        let function_run_result = run(FunctionRunParams {
+            function_path: Path::new("tests/fixtures/build/stack_overflow.wasm").to_path_buf(),
+            input: "{}".as_bytes().to_vec(),
+            export: DEFAULT_EXPORT,
+            max_wasm_stack: Some(64 * 1024),
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert!(!function_run_result.success);
+        assert!(function_run_result.logs.contains(
+            "The Function exceeded the maximum wasm stack size of 65536 bytes. \
+             Reduce its stack usage, e.g. by moving large locals to the heap, \
+             or raise the limit with --max-wasm-stack."
+        ));
+    }
+
+    #[test]
+    fn test_no_memory_dump_without_trap() {
+        let function_run_result = run(FunctionRunParams {
+            function_path: Path::new("tests/fixtures/build/no_output.wasm").to_path_buf(),
+            input: "{}".as_bytes().to_vec(),
+            export: DEFAULT_EXPORT,
+            dump_memory_on_trap: true,
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert!(function_run_result.memory_dump.is_none());
+    }
+
+    #[test]
+    fn test_preallocated_memory_is_not_reported_as_usage() {
+        let run_with_preallocation = |preallocate_memory_pages| {
+            run(FunctionRunParams {
+                function_path: Path::new("tests/fixtures/build/exit_code.wasm").to_path_buf(),
+                input: json!({ "code": 0 }).to_string().into(),
+                export: DEFAULT_EXPORT,
+                preallocate_memory_pages,
+                ..Default::default()
+            })
+            .unwrap()
+        };
+
+        let preallocated = run_with_preallocation(Some(1000));
+        assert!(preallocated.success);
+        assert_eq!(
+            preallocated.memory_usage,
+            run_with_preallocation(None).memory_usage
+        );
+    }
+
+    #[test]
+    fn test_preallocated_memory_is_not_reported_when_the_function_grows() {
+        let run_with_preallocation = |preallocate_memory_pages| {
+            run(FunctionRunParams {
+                function_path: Path::new("tests/fixtures/build/memory_grow.wasm").to_path_buf(),
+                input: "{}".as_bytes().to_vec(),
+                export: DEFAULT_EXPORT,
+                preallocate_memory_pages,
+                ..Default::default()
+            })
+            .unwrap()
+        };
+
+        let preallocated = run_with_preallocation(Some(10));
+        let not_preallocated = run_with_preallocation(None);
+        assert_eq!(not_preallocated.memory_usage, 192); // 3 * 64KiB pages
+        assert_eq!(preallocated.memory_usage, not_preallocated.memory_usage);
+        assert_eq!(preallocated.memory_growths, not_preallocated.memory_growths);
+        assert_eq!(preallocated.memory_growths, [(0, 1), (1, 3)]);
     }
 
     #[test]
@@ -345,6 +1054,60 @@ mod tests {
         assert_eq!(function_run_result.memory_usage, 12800); // 200 * 64KiB pages
     }
 
+    #[test]
+    fn test_module_hash() {
+        let function_path = Path::new("tests/fixtures/build/noop.wasm");
+        let function_run_result = run(FunctionRunParams {
+            function_path: function_path.to_path_buf(),
+            input: json!({ "count": 0 }).to_string().into(),
+            export: DEFAULT_EXPORT,
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(
+            function_run_result.module_hash,
+            format!(
+                "{:x}",
+                Sha256::digest(std::fs::read(function_path).unwrap())
+            )
+        );
+    }
+
+    #[test]
+    fn test_measure_compile() {
+        let compile_times =
+            measure_compile(Path::new("tests/fixtures/build/noop.wasm"), 3).unwrap();
+
+        assert_eq!(compile_times.len(), 3);
+    }
+
+    #[test]
+    fn test_function_name() {
+        assert_eq!(
+            function_name(Path::new("tests/fixtures/build/noop.wasm")),
+            "noop.wasm"
+        );
+        assert_eq!(function_name(Path::new("tests/fixtures/..")), "function");
+        assert_eq!(function_name(Path::new("/")), "function");
+    }
+
+    #[test]
+    fn test_memory_growths() {
+        let function_run_result = run(FunctionRunParams {
+            function_path: Path::new("tests/fixtures/build/linear_memory.wasm").to_path_buf(),
+            input: "{}".as_bytes().to_vec(),
+            export: DEFAULT_EXPORT,
+            preallocate_memory_pages: Some(300),
+            ..Default::default()
+        })
+        .unwrap();
+
+        // The preallocation isn't one of the Function's own growths
+        assert_eq!(function_run_result.memory_growths, [(0, 200)]);
+        assert_eq!(function_run_result.memory_growth_count, 1);
+    }
+
     #[test]
     fn test_logs_truncation() {
         let input = "{}".as_bytes().to_vec();
@@ -367,6 +1130,457 @@ mod tests {
         );
     }
 
+    #[derive(Default)]
+    struct CollectingSink(std::sync::Mutex<Vec<String>>);
+
+    impl LogSink for CollectingSink {
+        fn write_log(&self, log: &str) {
+            self.0.lock().unwrap().push(log.to_string());
+        }
+    }
+
+    #[test]
+    fn test_log_sink() {
+        let sink = Arc::new(CollectingSink::default());
+        let function_run_result = run(FunctionRunParams {
+            function_path: Path::new("tests/fixtures/build/log_truncation_function.wasm")
+                .to_path_buf(),
+            input: "{}".as_bytes().to_vec(),
+            export: DEFAULT_EXPORT,
+            log_sink: Some(sink.clone()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(sink.0.lock().unwrap().concat(), function_run_result.logs);
+    }
+
+    #[test]
+    fn test_log_sink_without_runtime_error() {
+        let sink = Arc::new(CollectingSink::default());
+        let function_run_result = run(FunctionRunParams {
+            function_path: Path::new("tests/fixtures/build/log_truncation_function.wasm")
+                .to_path_buf(),
+            input: "{}".as_bytes().to_vec(),
+            export: DEFAULT_EXPORT,
+            log_sink: Some(sink.clone()),
+            abort_on_first_log: true,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let function_logs = sink.0.lock().unwrap().concat();
+        assert!(!function_run_result.success);
+        assert!(!function_logs.is_empty());
+        assert!(!function_logs.contains(RUNTIME_ERROR_SEPARATOR));
+        assert!(function_run_result
+            .logs
+            .starts_with(&format!("{function_logs}\n{RUNTIME_ERROR_SEPARATOR}")));
+    }
+
+    #[test]
+    fn test_max_log_entries_excludes_runtime_error() {
+        let function_run_result = run(FunctionRunParams {
+            function_path: Path::new("tests/fixtures/build/log_truncation_function.wasm")
+                .to_path_buf(),
+            input: "{}".as_bytes().to_vec(),
+            export: DEFAULT_EXPORT,
+            max_log_entries: Some(1),
+            abort_on_first_log: true,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let (function_logs, error_logs) = function_run_result
+            .logs
+            .split_once(RUNTIME_ERROR_SEPARATOR)
+            .unwrap();
+        assert!(function_logs.starts_with('☠'));
+        assert!(error_logs.starts_with("The Function was aborted after its first log"));
+    }
+
+    #[test]
+    fn test_abort_on_first_log() {
+        let run_function = |abort_on_first_log| {
+            run(FunctionRunParams {
+                function_path: Path::new("tests/fixtures/build/log_truncation_function.wasm")
+                    .to_path_buf(),
+                input: "{}".as_bytes().to_vec(),
+                export: DEFAULT_EXPORT,
+                abort_on_first_log,
+                ..Default::default()
+            })
+            .unwrap()
+        };
+
+        let function_run_result = run_function(false);
+        let aborted_function_run_result = run_function(true);
+
+        assert!(!aborted_function_run_result.success);
+        assert!(aborted_function_run_result.instructions < function_run_result.instructions);
+        assert!(aborted_function_run_result.logs.ends_with(&format!(
+            "The Function was aborted after its first log, at {} instructions.",
+            aborted_function_run_result.instructions
+        )));
+    }
+
+    #[test]
+    fn test_logs_with_runtime_error() {
+        let mut logs = LogStream::with_max_bytesize(4);
+        assert_eq!(logs_with_runtime_error(&logs, ""), "");
+        assert_eq!(
+            logs_with_runtime_error(&logs, "trap"),
+            "--- runtime error ---\ntrap"
+        );
+
+        logs.append(b"hello world");
+        assert_eq!(logs_with_runtime_error(&logs, ""), "orld");
+        assert_eq!(
+            logs_with_runtime_error(&logs, "trap"),
+            "orld\n--- runtime error ---\ntrap"
+        );
+    }
+
+    #[test]
+    fn test_runtime_error_separator() {
+        let function_run_result = run(FunctionRunParams {
+            function_path: Path::new("tests/fixtures/build/log_truncation_function.wasm")
+                .to_path_buf(),
+            input: "{}".as_bytes().to_vec(),
+            export: DEFAULT_EXPORT,
+            max_instructions: Some(100_000),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let (function_logs, runtime_error) = function_run_result
+            .logs
+            .split_once("\n--- runtime error ---\n")
+            .unwrap();
+        assert!(!function_logs.is_empty());
+        assert!(
+            runtime_error.ends_with("The Function exceeded the hard limit of 100000 instructions.")
+        );
+    }
+
+    #[test]
+    fn test_preopens() {
+        let preopens = ["/=tests/fixtures/input/preopen".parse::<Preopen>().unwrap()];
+        let run_export = |export| {
+            run(FunctionRunParams {
+                function_path: Path::new("tests/fixtures/build/preopen.wasm").to_path_buf(),
+                input: "{}".as_bytes().to_vec(),
+                export,
+                preopens: &preopens,
+                ..Default::default()
+            })
+            .unwrap()
+        };
+
+        assert_eq!(
+            run_export(DEFAULT_EXPORT).output_json(),
+            Some(&json!({"configured": true}))
+        );
+        assert_eq!(
+            run_export("write_file").output_json(),
+            Some(&json!({"denied": true}))
+        );
+        assert!(!Path::new("tests/fixtures/input/preopen/out.json").exists());
+    }
+
+    #[test]
+    fn test_stub_unknown_imports() {
+        let run_export = |export, stub_unknown_imports| {
+            run(FunctionRunParams {
+                function_path: Path::new("tests/fixtures/build/unknown_import.wasm").to_path_buf(),
+                input: "{}".as_bytes().to_vec(),
+                export,
+                stub_unknown_imports,
+                ..Default::default()
+            })
+        };
+
+        assert!(run_export(DEFAULT_EXPORT, false).is_err());
+
+        let function_run_result = run_export(DEFAULT_EXPORT, true).unwrap();
+        assert_eq!(function_run_result.logs, "");
+
+        let function_run_result = run_export("call_missing", true).unwrap();
+        assert!(!function_run_result.success);
+        assert!(
+            function_run_result
+                .logs
+                .contains("called unimplemented import env.missing"),
+            "{}",
+            function_run_result.logs
+        );
+    }
+
+    #[test]
+    fn test_export_signature_mismatch() {
+        let error = run(FunctionRunParams {
+            function_path: Path::new("tests/fixtures/build/signature.wasm").to_path_buf(),
+            input: "{}".as_bytes().to_vec(),
+            export: "run",
+            ..Default::default()
+        })
+        .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Export `run` has signature (i32, i64) -> i32, expected () -> ()"
+        );
+    }
+
+    #[test]
+    fn test_function_exports() {
+        let exports = function_exports(Path::new("tests/fixtures/build/export_all.wasm")).unwrap();
+
+        let names: Vec<&str> = exports.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["a", "b", "with_param"]);
+        assert!(exports[0].1.is_ok());
+        assert!(exports[1].1.is_ok());
+        assert_eq!(
+            exports[2].1.as_ref().unwrap_err().to_string(),
+            "Export `with_param` has signature (i32) -> (), expected () -> ()"
+        );
+    }
+
+    fn try_run_params<'a>(function_path: &str, export: &'a str) -> FunctionRunParams<'a> {
+        FunctionRunParams {
+            function_path: Path::new(function_path).to_path_buf(),
+            input: "{}".as_bytes().to_vec(),
+            export,
+            max_wasm_stack: Some(64 * 1024),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_try_run_module_load_error() {
+        let error = try_run(try_run_params(
+            "tests/fixtures/build/missing.wasm",
+            DEFAULT_EXPORT,
+        ))
+        .unwrap_err();
+
+        assert!(matches!(error, RunError::ModuleLoad(_)));
+        assert!(error
+            .to_string()
+            .ends_with("failed to read from `tests/fixtures/build/missing.wasm`: No such file or directory (os error 2)"));
+    }
+
+    #[test]
+    fn test_try_run_invalid_input_error() {
+        let error = try_run(FunctionRunParams {
+            input: "not json".as_bytes().to_vec(),
+            ..try_run_params("tests/fixtures/build/noop.wasm", DEFAULT_EXPORT)
+        })
+        .unwrap_err();
+
+        assert!(matches!(error, RunError::InvalidInput(_)));
+        assert!(error.to_string().starts_with("Invalid input JSON: "));
+    }
+
+    #[test]
+    fn test_try_run_forbidden_import_error() {
+        assert!(matches!(
+            try_run(FunctionRunParams {
+                forbid_wasi: true,
+                ..try_run_params("tests/fixtures/build/noop.wasm", DEFAULT_EXPORT)
+            }),
+            Err(RunError::ForbiddenImport(_))
+        ));
+    }
+
+    #[test]
+    fn test_try_run_invalid_export_error() {
+        assert!(matches!(
+            try_run(try_run_params("tests/fixtures/build/signature.wasm", "run")),
+            Err(RunError::InvalidExport(_))
+        ));
+        assert!(matches!(
+            try_run(try_run_params("tests/fixtures/build/noop.wasm", "missing")),
+            Err(RunError::InvalidExport(_))
+        ));
+        assert!(matches!(
+            try_run(FunctionRunParams {
+                export_index: Some(999),
+                ..try_run_params("tests/fixtures/build/noop.wasm", DEFAULT_EXPORT)
+            }),
+            Err(RunError::InvalidExport(_))
+        ));
+    }
+
+    #[test]
+    fn test_try_run_setup_error() {
+        let preopens = [Preopen {
+            guest_path: "/data".to_string(),
+            host_path: Path::new("tests/fixtures/missing").to_path_buf(),
+        }];
+        let error = try_run(FunctionRunParams {
+            preopens: &preopens,
+            ..try_run_params("tests/fixtures/build/noop.wasm", DEFAULT_EXPORT)
+        })
+        .unwrap_err();
+
+        assert!(matches!(error, RunError::Setup(_)));
+        assert!(error
+            .source()
+            .unwrap()
+            .to_string()
+            .starts_with("Couldn't open preopened directory"));
+    }
+
+    #[test]
+    fn test_try_run_instantiation_error() {
+        let error = try_run(try_run_params(
+            "tests/fixtures/build/unknown_import.wasm",
+            DEFAULT_EXPORT,
+        ))
+        .unwrap_err();
+
+        assert!(matches!(error, RunError::Instantiation(_)));
+        assert!(error.source().is_some());
+    }
+
+    #[test]
+    fn test_try_run_trap_error() {
+        assert!(matches!(
+            try_run(try_run_params(
+                "tests/fixtures/build/stack_overflow.wasm",
+                DEFAULT_EXPORT
+            )),
+            Err(RunError::Trap { .. })
+        ));
+    }
+
+    #[test]
+    fn test_try_run_out_of_fuel_error() {
+        assert!(matches!(
+            try_run(FunctionRunParams {
+                max_instructions: Some(1),
+                ..try_run_params("tests/fixtures/build/noop.wasm", DEFAULT_EXPORT)
+            }),
+            Err(RunError::OutOfFuel { .. })
+        ));
+    }
+
+    #[test]
+    fn test_try_run_timeout_error() {
+        let error = try_run(FunctionRunParams {
+            timeout: Some(Duration::from_millis(50)),
+            ..try_run_params("tests/fixtures/build/infinite_loop.wasm", DEFAULT_EXPORT)
+        })
+        .unwrap_err();
+
+        assert!(matches!(error, RunError::Timeout { .. }));
+        assert!(error
+            .result()
+            .unwrap()
+            .logs
+            .contains("The Function exceeded the timeout of 50ms, at "));
+    }
+
+    #[test]
+    fn test_try_run_interrupt_without_timeout_is_trap() {
+        assert!(matches!(
+            try_run(FunctionRunParams {
+                abort_on_first_log: true,
+                ..try_run_params(
+                    "tests/fixtures/build/log_truncation_function.wasm",
+                    DEFAULT_EXPORT
+                )
+            }),
+            Err(RunError::Trap { .. })
+        ));
+    }
+
+    #[test]
+    fn test_try_run_invalid_output_error() {
+        let error = try_run(try_run_params(
+            "tests/fixtures/build/exports.wasm",
+            "export1",
+        ))
+        .unwrap_err();
+
+        assert!(matches!(error, RunError::InvalidOutput { .. }));
+        assert_eq!(error.result().unwrap().name, "exports.wasm");
+    }
+
+    #[test]
+    fn test_try_run() {
+        assert!(try_run(FunctionRunParams {
+            timeout: Some(Duration::from_secs(60)),
+            ..try_run_params("tests/fixtures/build/noop.wasm", DEFAULT_EXPORT)
+        })
+        .is_ok());
+    }
+
+    #[test]
+    fn test_run_until_instructions() {
+        let function_run_result = run(FunctionRunParams {
+            function_path: Path::new("tests/fixtures/build/log_truncation_function.wasm")
+                .to_path_buf(),
+            input: "{}".as_bytes().to_vec(),
+            export: DEFAULT_EXPORT,
+            dump_memory_on_trap: true,
+            run_until_instructions: Some(5000),
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert!(function_run_result.success);
+        assert_eq!(function_run_result.instructions, 5000);
+        assert_eq!(
+            function_run_result.warnings,
+            ["The Function was stopped at 5000 instructions"]
+        );
+        assert!(function_run_result.memory_dump.is_some());
+    }
+
+    #[test]
+    fn test_max_instructions() {
+        let function_run_result = run(FunctionRunParams {
+            function_path: Path::new("tests/fixtures/build/noop.wasm").to_path_buf(),
+            input: json!({ "count": 0 }).to_string().into(),
+            export: DEFAULT_EXPORT,
+            max_instructions: Some(1000),
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert!(!function_run_result.success);
+        assert_eq!(function_run_result.instructions, 1000);
+        assert_eq!(function_run_result.max_instructions_hard, Some(1000));
+        assert!(function_run_result
+            .logs
+            .contains("The Function exceeded the hard limit of 1000 instructions."));
+    }
+
+    #[test]
+    fn test_max_instructions_soft() {
+        let run_function = |max_instructions_soft| {
+            run(FunctionRunParams {
+                function_path: Path::new("tests/fixtures/build/noop.wasm").to_path_buf(),
+                input: json!({ "count": 0 }).to_string().into(),
+                export: DEFAULT_EXPORT,
+                max_instructions_soft: Some(max_instructions_soft),
+                ..Default::default()
+            })
+            .unwrap()
+        };
+
+        let function_run_result = run_function(1000);
+        assert!(function_run_result.success);
+        assert_eq!(function_run_result.max_instructions_soft, Some(1000));
+        assert_eq!(
+            function_run_result.warnings,
+            ["Instructions exceeded the soft limit of 1000"]
+        );
+        assert!(run_function(u64::MAX).warnings.is_empty());
+    }
+
     #[test]
     fn test_file_size_in_kb() {
         let file_path = Path::new("tests/fixtures/build/exit_code.wasm");
@@ -384,4 +1598,21 @@ mod tests {
             file_path.metadata().unwrap().len() / 1024
         );
     }
+
+    #[test]
+    fn test_compile_time_is_reported_separately() {
+        let function_run_result = run(FunctionRunParams {
+            function_path: Path::new("tests/fixtures/build/exit_code.wasm").to_path_buf(),
+            input: json!({ "code": 0 }).to_string().into(),
+            export: DEFAULT_EXPORT,
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert!(function_run_result.compile_us > 0);
+        assert!(function_run_result.to_string().contains(&format!(
+            "Compile Time: {:.2}ms",
+            function_run_result.compile_us as f64 / 1000.0
+        )));
+    }
 }
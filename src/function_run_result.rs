@@ -1,6 +1,13 @@
-use colored::Colorize;
+use anyhow::{anyhow, Result};
+use base64::{prelude::BASE64_STANDARD, Engine};
+use colored::{ColoredString, Colorize};
 use serde::{Deserialize, Serialize};
-use std::fmt;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Write},
+    io::Cursor,
+};
 
 const FUNCTION_LOG_LIMIT: usize = 1_000;
 
@@ -17,18 +24,115 @@ pub enum FunctionOutput {
     InvalidJsonOutput(InvalidOutput),
 }
 
+/// How the input bytes passed to the Function are encoded.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum InputCodec {
+    #[default]
+    Json,
+    Messagepack,
+    /// Arbitrary bytes, echoed as a base64 string.
+    Raw,
+}
+
+impl InputCodec {
+    /// Decodes the input bytes into the value echoed in the run result.
+    pub fn decode(self, input: &[u8]) -> Result<serde_json::Value> {
+        match self {
+            InputCodec::Json => {
+                serde_json::from_slice(input).map_err(|e| anyhow!("Invalid input JSON: {}", e))
+            }
+            InputCodec::Messagepack => {
+                let mut cursor = Cursor::new(input);
+                let value = rmp_serde::decode::from_read(&mut cursor)
+                    .map_err(|e| anyhow!("Invalid input MessagePack: {}", e))?;
+                if cursor.position() as usize != input.len() {
+                    return Err(anyhow!(
+                        "Invalid input MessagePack: trailing bytes after the first value"
+                    ));
+                }
+                Ok(value)
+            }
+            InputCodec::Raw => Ok(serde_json::Value::String(BASE64_STANDARD.encode(input))),
+        }
+    }
+
+    /// Encodes an echoed input back into the bytes passed to the Function, the inverse of [`InputCodec::decode`].
+    pub fn encode(self, input: &serde_json::Value) -> Result<Vec<u8>> {
+        match self {
+            InputCodec::Json => {
+                serde_json::to_vec(input).map_err(|e| anyhow!("Couldn't serialize JSON: {}", e))
+            }
+            InputCodec::Messagepack => rmp_serde::to_vec(input)
+                .map_err(|e| anyhow!("Couldn't convert JSON to MessagePack: {}", e)),
+            InputCodec::Raw => {
+                let input = input
+                    .as_str()
+                    .ok_or_else(|| anyhow!("Raw input should be echoed as a base64 string"))?;
+                BASE64_STANDARD
+                    .decode(input)
+                    .map_err(|e| anyhow!("Invalid base64 input: {}", e))
+            }
+        }
+    }
+}
+
+impl fmt::Display for InputCodec {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputCodec::Json => write!(formatter, "json"),
+            InputCodec::Messagepack => write!(formatter, "messagepack"),
+            InputCodec::Raw => write!(formatter, "raw"),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct FunctionRunResult {
     pub name: String,
     pub size: u64,
+    /// SHA-256 of the module's bytes, tying the result to the exact build that produced it.
+    #[serde(default)]
+    pub module_hash: String,
     pub memory_usage: u64,
     pub instructions: u64,
+    /// The instruction count above which the run result includes a warning, if one was set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_instructions_soft: Option<u64>,
+    /// The instruction count after which the Function is stopped, if one was set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_instructions_hard: Option<u64>,
+    #[serde(default)]
+    pub runtime_us: u64,
+    #[serde(default)]
+    pub compile_us: u64,
     pub logs: String,
     pub input: serde_json::Value,
     pub output: FunctionOutput,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deterministic: Option<bool>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metrics: BTreeMap<String, serde_json::Value>,
+    #[serde(default)]
+    pub memory_growth_count: u64,
+    /// Every memory growth as a `(from, to)` page count, in order.
+    #[serde(skip)]
+    pub memory_growths: Vec<(u64, u64)>,
+    /// The code the Function exited with, if it called `proc_exit` rather than returning.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// The codec the input was passed with, which `input` was decoded from. Missing from older run results.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_codec: Option<InputCodec>,
     #[serde(skip)]
     pub profile: Option<String>,
     #[serde(skip)]
+    pub memory_dump: Option<Vec<u8>>,
+    #[serde(skip)]
     pub scale_factor: f64,
     pub success: bool,
 }
@@ -36,6 +140,43 @@ pub struct FunctionRunResult {
 const DEFAULT_INSTRUCTIONS_LIMIT: u64 = 11_000_000;
 const DEFAULT_INPUT_SIZE_LIMIT: u64 = 128_000;
 const DEFAULT_OUTPUT_SIZE_LIMIT: u64 = 20_000;
+pub const DEFAULT_RUNTIME_THRESHOLD_MS: f64 = 5.0;
+
+/// Controls how a [`FunctionRunResult`] is rendered for humans.
+#[derive(Clone, Debug)]
+pub struct DisplayOptions {
+    /// Print the Input section.
+    pub show_input: bool,
+    /// Print the Logs section.
+    pub show_logs: bool,
+    /// Print the Output section.
+    pub show_output: bool,
+    /// Print the Resource Limits section.
+    pub show_limits: bool,
+    /// Print the Benchmark Results section.
+    pub show_benchmark: bool,
+    /// Number of spaces used to indent the pretty-printed input and output JSON.
+    pub indent: usize,
+    /// Print instruction counts in full instead of rounding them to K/M/B.
+    pub exact_counts: bool,
+    /// Runtime in milliseconds above which the runtime is highlighted.
+    pub runtime_threshold_ms: f64,
+}
+
+impl Default for DisplayOptions {
+    fn default() -> Self {
+        Self {
+            show_input: true,
+            show_logs: true,
+            show_output: true,
+            show_limits: true,
+            show_benchmark: true,
+            indent: 2,
+            exact_counts: false,
+            runtime_threshold_ms: DEFAULT_RUNTIME_THRESHOLD_MS,
+        }
+    }
+}
 
 pub fn get_json_size_as_bytes(value: &serde_json::Value) -> usize {
     serde_json::to_vec(value).map(|v| v.len()).unwrap_or(0)
@@ -46,8 +187,191 @@ impl FunctionRunResult {
         serde_json::to_string_pretty(&self).unwrap_or_else(|error| error.to_string())
     }
 
+    pub fn to_json_compact(&self) -> String {
+        serde_json::to_string(&self).unwrap_or_else(|error| error.to_string())
+    }
+
+    /// The input as JSON, or `None` when it was passed raw and is only echoed as base64.
+    pub fn input_json(&self) -> Option<&serde_json::Value> {
+        match self.input_codec {
+            Some(InputCodec::Raw) => None,
+            _ => Some(&self.input),
+        }
+    }
+
+    /// The output as JSON, or `None` when the Function didn't produce valid JSON.
+    pub fn output_json(&self) -> Option<&serde_json::Value> {
+        match &self.output {
+            FunctionOutput::JsonOutput(value) => Some(value),
+            FunctionOutput::InvalidJsonOutput(_) => None,
+        }
+    }
+
     pub fn input_size(&self) -> usize {
-        get_json_size_as_bytes(&self.input)
+        match self.input_codec {
+            // Raw inputs are echoed as base64, their size is that of the bytes passed to the Function
+            Some(InputCodec::Raw) => InputCodec::Raw
+                .encode(&self.input)
+                .map_or(0, |input| input.len()),
+            _ => get_json_size_as_bytes(&self.input),
+        }
+    }
+
+    /// Renders the run for humans, using terminal colors for section titles and exceeded limits when `colorize`
+    /// is set.
+    pub fn render(&self, opts: &DisplayOptions, colorize: bool) -> String {
+        let mut rendered = String::new();
+        self.write_report(&mut rendered, opts, colorize)
+            .expect("Writing to a String should not fail");
+        rendered
+    }
+
+    /// Describes how this run's metrics changed relative to a previously saved run, flagging every metric that grew
+    /// by more than `threshold_percent`.
+    pub fn render_comparison(
+        &self,
+        baseline: &FunctionRunResult,
+        threshold_percent: f64,
+        colorize: bool,
+    ) -> String {
+        [
+            humanize_delta(
+                "Instructions",
+                self.instructions,
+                baseline.instructions,
+                threshold_percent,
+                colorize,
+            ),
+            humanize_delta(
+                "Linear Memory Usage (KB)",
+                self.memory_usage,
+                baseline.memory_usage,
+                threshold_percent,
+                colorize,
+            ),
+            humanize_delta(
+                "Output Size (B)",
+                self.output_size() as u64,
+                baseline.output_size() as u64,
+                threshold_percent,
+                colorize,
+            ),
+        ]
+        .join("\n")
+    }
+
+    /// SHA-256 of the output, hex encoded.
+    ///
+    /// JSON output is hashed in its canonical form (minified with sorted keys), so the hash only changes when the
+    /// output itself does. Invalid output is hashed as the raw stdout.
+    pub fn compute_output_hash(&self) -> String {
+        let bytes = match &self.output {
+            FunctionOutput::JsonOutput(value) => serde_json::to_vec(value).unwrap_or_default(),
+            FunctionOutput::InvalidJsonOutput(invalid_output) => {
+                invalid_output.stdout.as_bytes().to_vec()
+            }
+        };
+
+        format!("{:x}", Sha256::digest(bytes))
+    }
+
+    /// Describes how this run's output differs from `other`'s, one line per differing JSON Pointer.
+    pub fn output_diff(&self, other: &FunctionRunResult) -> Vec<String> {
+        let mut diff = Vec::new();
+        match (self.output_json(), other.output_json()) {
+            (Some(output), Some(other_output)) => {
+                diff_json(String::new(), output, other_output, &mut diff)
+            }
+            _ if self.compute_output_hash() != other.compute_output_hash() => {
+                let output_text = |output: &FunctionOutput| match output {
+                    FunctionOutput::JsonOutput(value) => value.to_string(),
+                    FunctionOutput::InvalidJsonOutput(invalid_output) => {
+                        format!("{:?}", invalid_output.stdout)
+                    }
+                };
+                diff.push(format!(
+                    "/: {} != {}",
+                    output_text(&self.output),
+                    output_text(&other.output)
+                ));
+            }
+            _ => {}
+        }
+        diff
+    }
+
+    /// Replaces the value at `pointer` in the input and output with `"[REDACTED]"`, where it exists.
+    ///
+    /// Fails when the input is raw, as it can't be redacted, or when `pointer` matches neither the input nor the
+    /// output, so values meant to be scrubbed are never shown by mistake.
+    pub fn redact(&mut self, pointer: &str) -> Result<()> {
+        let redacted = || serde_json::Value::String("[REDACTED]".to_string());
+
+        if self.input_codec == Some(InputCodec::Raw) {
+            return Err(anyhow!(
+                "Couldn't redact `{}`: raw inputs can't be redacted",
+                pointer
+            ));
+        }
+
+        let mut matched = false;
+        if let Some(value) = self.input.pointer_mut(pointer) {
+            *value = redacted();
+            matched = true;
+        }
+        if let FunctionOutput::JsonOutput(output) = &mut self.output {
+            if let Some(value) = output.pointer_mut(pointer) {
+                *value = redacted();
+                matched = true;
+            }
+        }
+
+        if !matched {
+            return Err(anyhow!(
+                "Couldn't redact `{}`: it matches neither the input nor the output",
+                pointer
+            ));
+        }
+        Ok(())
+    }
+
+    /// Moves log lines starting with `prefix` and holding a `key=value` pair into the metrics, where values that
+    /// are valid JSON (e.g. numbers) are kept as such and others as strings.
+    pub fn extract_metrics(&mut self, prefix: &str) {
+        let mut logs = String::with_capacity(self.logs.len());
+        for line in self.logs.split_inclusive('\n') {
+            let metric = line
+                .trim_end_matches(['\r', '\n'])
+                .strip_prefix(prefix)
+                .and_then(|metric| metric.split_once('='));
+            match metric {
+                Some((key, value)) => {
+                    let value = serde_json::from_str(value)
+                        .unwrap_or_else(|_| serde_json::Value::String(value.to_string()));
+                    self.metrics.insert(key.to_string(), value);
+                }
+                None => logs.push_str(line),
+            }
+        }
+        self.logs = logs;
+    }
+
+    /// The `count` stacks with the highest sample weight in the collapsed profile, hottest first.
+    pub fn hottest_stacks(&self, count: usize) -> Vec<(&str, u64)> {
+        let mut stacks: Vec<(&str, u64)> = self
+            .profile
+            .as_deref()
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| {
+                let (stack, weight) = line.rsplit_once(' ')?;
+                Some((stack, weight.parse().ok()?))
+            })
+            .collect();
+
+        stacks.sort_by(|a, b| b.1.cmp(&a.1));
+        stacks.truncate(count);
+        stacks
     }
 
     pub fn output_size(&self) -> usize {
@@ -58,7 +382,56 @@ impl FunctionRunResult {
     }
 }
 
-fn humanize_size(title: &str, size_bytes: u64, size_limit: u64) -> String {
+fn humanize_delta(
+    title: &str,
+    current: u64,
+    baseline: u64,
+    threshold_percent: f64,
+    colorize: bool,
+) -> String {
+    let change_percent = if baseline == 0 {
+        if current == 0 {
+            0.0
+        } else {
+            f64::INFINITY
+        }
+    } else {
+        (current as f64 - baseline as f64) / baseline as f64 * 100.0
+    };
+
+    let line = format!(
+        "{}: {} -> {} ({:+.2}%)",
+        title, baseline, current, change_percent
+    );
+
+    if change_percent > threshold_percent {
+        paint(format!("{line} REGRESSION").red(), colorize).to_string()
+    } else {
+        line
+    }
+}
+
+/// Drops the colors of `text` unless `colorize` is set.
+fn paint(text: ColoredString, colorize: bool) -> ColoredString {
+    if colorize {
+        text
+    } else {
+        text.clear()
+    }
+}
+
+fn to_string_indented(value: &serde_json::Value, indent: usize) -> String {
+    let indent = " ".repeat(indent);
+    let mut buffer = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+    let mut serializer = serde_json::Serializer::with_formatter(&mut buffer, formatter);
+    value
+        .serialize(&mut serializer)
+        .expect("Value should be serializable to a string");
+    String::from_utf8(buffer).expect("Serialized JSON should be valid UTF-8")
+}
+
+fn humanize_size(title: &str, size_bytes: u64, size_limit: u64, colorize: bool) -> String {
     let size_humanized = match size_bytes {
         0..=1023 => format!("{}B", size_bytes),
         1024..=1_048_575 => format!("{:.2}KB", size_bytes as f64 / 1024.0),
@@ -69,14 +442,31 @@ fn humanize_size(title: &str, size_bytes: u64, size_limit: u64) -> String {
     };
 
     if size_bytes > size_limit {
-        format!("{}: {}", title, size_humanized).red().to_string()
+        paint(format!("{}: {}", title, size_humanized).red(), colorize).to_string()
     } else {
         format!("{}: {}", title, size_humanized)
     }
 }
 
-fn humanize_instructions(title: &str, instructions: u64, instructions_limit: u64) -> String {
+fn humanize_runtime(title: &str, runtime_us: u64, threshold_ms: f64, colorize: bool) -> String {
+    let runtime_ms = runtime_us as f64 / 1000.0;
+
+    if runtime_ms > threshold_ms {
+        paint(format!("{}: {:.2}ms", title, runtime_ms).red(), colorize).to_string()
+    } else {
+        format!("{}: {:.2}ms", title, runtime_ms)
+    }
+}
+
+fn humanize_instructions(
+    title: &str,
+    instructions: u64,
+    instructions_limit: u64,
+    exact: bool,
+    colorize: bool,
+) -> String {
     let instructions_humanized = match instructions {
+        _ if exact => instructions.to_string(),
         0..=999 => instructions.to_string(),
         1000..=999_999 => format!("{}K", instructions as f64 / 1000.0),
         1_000_000..=999_999_999 => format!("{}M", instructions as f64 / 1_000_000.0),
@@ -84,9 +474,11 @@ fn humanize_instructions(title: &str, instructions: u64, instructions_limit: u64
     };
 
     if instructions > instructions_limit {
-        format!("{}: {}", title, instructions_humanized)
-            .red()
-            .to_string()
+        paint(
+            format!("{}: {}", title, instructions_humanized).red(),
+            colorize,
+        )
+        .to_string()
     } else {
         format!("{}: {}", title, instructions_humanized)
     }
@@ -94,56 +486,89 @@ fn humanize_instructions(title: &str, instructions: u64, instructions_limit: u64
 
 impl fmt::Display for FunctionRunResult {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        writeln!(
-            formatter,
-            "{}\n\n{}",
-            "            Input            ".black().on_bright_yellow(),
-            serde_json::to_string_pretty(&self.input)
-                .expect("Input should be serializable to a string")
-        )?;
-
-        writeln!(
-            formatter,
-            "{}\n\n{}\n",
-            "            Logs            ".black().on_bright_blue(),
-            self.logs
-        )?;
-
-        let logs_length = self.logs.len();
-        if logs_length > FUNCTION_LOG_LIMIT {
+        self.write_report(formatter, &DisplayOptions::default(), true)
+    }
+}
+
+impl FunctionRunResult {
+    fn write_report(
+        &self,
+        formatter: &mut dyn Write,
+        opts: &DisplayOptions,
+        colorize: bool,
+    ) -> fmt::Result {
+        if opts.show_input {
             writeln!(
                 formatter,
-                "{}\n\n",
-                &format!(
-                    "Logs would be truncated in production, length {logs_length} > {FUNCTION_LOG_LIMIT} limit",
-                ).red()
+                "{}\n\n{}",
+                paint(
+                    "            Input            ".black().on_bright_yellow(),
+                    colorize
+                ),
+                to_string_indented(&self.input, opts.indent)
             )?;
         }
 
-        match &self.output {
-            FunctionOutput::JsonOutput(json_output) => {
+        if opts.show_logs {
+            writeln!(
+                formatter,
+                "{}\n\n{}\n",
+                paint(
+                    "            Logs            ".black().on_bright_blue(),
+                    colorize
+                ),
+                self.logs
+            )?;
+
+            let logs_length = self.logs.len();
+            if logs_length > FUNCTION_LOG_LIMIT {
                 writeln!(
                     formatter,
-                    "{}\n\n{}",
-                    "           Output           ".black().on_bright_green(),
-                    serde_json::to_string_pretty(&json_output)
-                        .expect("Output should be serializable to a string")
+                    "{}\n\n",
+                    paint(
+                        format!(
+                            "Logs would be truncated in production, length {logs_length} > {FUNCTION_LOG_LIMIT} limit",
+                        ).red(),
+                        colorize,
+                    )
                 )?;
             }
-            FunctionOutput::InvalidJsonOutput(invalid_output) => {
-                writeln!(
-                    formatter,
-                    "{}\n\n{}",
-                    "        Invalid Output      ".black().on_bright_red(),
-                    invalid_output.stdout
-                )?;
+        }
 
-                writeln!(
-                    formatter,
-                    "{}\n\n{}",
-                    "         JSON Error         ".black().on_bright_red(),
-                    invalid_output.error
-                )?;
+        if opts.show_output {
+            match &self.output {
+                FunctionOutput::JsonOutput(json_output) => {
+                    writeln!(
+                        formatter,
+                        "{}\n\n{}",
+                        paint(
+                            "           Output           ".black().on_bright_green(),
+                            colorize
+                        ),
+                        to_string_indented(json_output, opts.indent)
+                    )?;
+                }
+                FunctionOutput::InvalidJsonOutput(invalid_output) => {
+                    writeln!(
+                        formatter,
+                        "{}\n\n{}",
+                        paint(
+                            "        Invalid Output      ".black().on_bright_red(),
+                            colorize
+                        ),
+                        invalid_output.stdout
+                    )?;
+
+                    writeln!(
+                        formatter,
+                        "{}\n\n{}",
+                        paint(
+                            "         JSON Error         ".black().on_bright_red(),
+                            colorize
+                        ),
+                        invalid_output.error
+                    )?;
+                }
             }
         }
 
@@ -151,84 +576,195 @@ impl fmt::Display for FunctionRunResult {
         let output_size_limit = self.scale_factor * DEFAULT_OUTPUT_SIZE_LIMIT as f64;
         let instructions_size_limit = self.scale_factor * DEFAULT_INSTRUCTIONS_LIMIT as f64;
 
-        writeln!(
-            formatter,
-            "\n{}\n\n",
-            "        Resource Limits        "
-                .black()
-                .on_bright_magenta()
-        )?;
-
-        writeln!(
-            formatter,
-            "{}",
-            humanize_size(
-                "Input Size",
-                input_size_limit as u64,
-                input_size_limit as u64
-            )
-        )?;
-
-        writeln!(
-            formatter,
-            "{}",
-            humanize_size(
-                "Output Size",
-                output_size_limit as u64,
-                output_size_limit as u64
-            )
-        )?;
-        writeln!(
-            formatter,
-            "{}",
-            humanize_instructions(
-                "Instructions",
-                instructions_size_limit as u64,
-                instructions_size_limit as u64
-            )
-        )?;
-
-        let title = "     Benchmark Results      "
-            .black()
-            .on_truecolor(150, 191, 72);
-
-        write!(formatter, "\n\n{title}\n\n")?;
-        writeln!(formatter, "Name: {}", self.name)?;
-        writeln!(formatter, "Linear Memory Usage: {}KB", self.memory_usage)?;
-        writeln!(
-            formatter,
-            "{}",
-            humanize_instructions(
-                "Instructions",
-                self.instructions,
-                instructions_size_limit as u64
-            )
-        )?;
-        writeln!(
-            formatter,
-            "{}",
-            humanize_size(
-                "Input Size",
-                self.input_size() as u64,
-                input_size_limit as u64,
-            )
-        )?;
-        writeln!(
-            formatter,
-            "{}",
-            humanize_size(
-                "Output Size",
-                self.output_size() as u64,
-                output_size_limit as u64,
-            )
-        )?;
+        if opts.show_limits {
+            writeln!(
+                formatter,
+                "\n{}\n\n",
+                paint(
+                    "        Resource Limits        "
+                        .black()
+                        .on_bright_magenta(),
+                    colorize
+                )
+            )?;
 
-        writeln!(formatter, "Module Size: {}KB\n", self.size)?;
+            writeln!(
+                formatter,
+                "{}",
+                humanize_size(
+                    "Input Size",
+                    input_size_limit as u64,
+                    input_size_limit as u64,
+                    colorize,
+                )
+            )?;
+
+            writeln!(
+                formatter,
+                "{}",
+                humanize_size(
+                    "Output Size",
+                    output_size_limit as u64,
+                    output_size_limit as u64,
+                    colorize,
+                )
+            )?;
+            writeln!(
+                formatter,
+                "{}",
+                humanize_instructions(
+                    "Instructions",
+                    instructions_size_limit as u64,
+                    instructions_size_limit as u64,
+                    opts.exact_counts,
+                    colorize,
+                )
+            )?;
+
+            for (title, limit) in [
+                ("Instructions Soft Limit", self.max_instructions_soft),
+                ("Instructions Hard Limit", self.max_instructions_hard),
+            ] {
+                if let Some(limit) = limit {
+                    writeln!(
+                        formatter,
+                        "{}",
+                        humanize_instructions(title, limit, u64::MAX, opts.exact_counts, colorize)
+                    )?;
+                }
+            }
+        }
+
+        if opts.show_benchmark {
+            let title = paint(
+                "     Benchmark Results      "
+                    .black()
+                    .on_truecolor(150, 191, 72),
+                colorize,
+            );
+
+            write!(formatter, "\n\n{title}\n\n")?;
+            writeln!(formatter, "Name: {}", self.name)?;
+            writeln!(formatter, "Linear Memory Usage: {}KB", self.memory_usage)?;
+            writeln!(formatter, "Memory Growths: {}", self.memory_growth_count)?;
+            writeln!(
+                formatter,
+                "{}",
+                humanize_instructions(
+                    "Instructions",
+                    self.instructions,
+                    instructions_size_limit as u64,
+                    opts.exact_counts,
+                    colorize,
+                )
+            )?;
+            writeln!(
+                formatter,
+                "{}",
+                humanize_size(
+                    "Input Size",
+                    self.input_size() as u64,
+                    input_size_limit as u64,
+                    colorize,
+                )
+            )?;
+            writeln!(
+                formatter,
+                "{}",
+                humanize_size(
+                    "Output Size",
+                    self.output_size() as u64,
+                    output_size_limit as u64,
+                    colorize,
+                )
+            )?;
+
+            if let Some(output_hash) = &self.output_hash {
+                writeln!(formatter, "Output Hash: {output_hash}")?;
+            }
+
+            if let Some(input_codec) = &self.input_codec {
+                writeln!(formatter, "Input Codec: {input_codec}")?;
+            }
+
+            if let Some(exit_code) = self.exit_code {
+                writeln!(formatter, "Exit Code: {exit_code}")?;
+            }
+
+            if let Some(deterministic) = self.deterministic {
+                writeln!(formatter, "Deterministic: {deterministic}")?;
+            }
+
+            for (key, value) in &self.metrics {
+                writeln!(formatter, "Metric {key}: {value}")?;
+            }
+
+            for warning in &self.warnings {
+                writeln!(
+                    formatter,
+                    "{}",
+                    paint(format!("Warning: {warning}").yellow(), colorize)
+                )?;
+            }
+
+            writeln!(
+                formatter,
+                "{}",
+                humanize_runtime(
+                    "Runtime",
+                    self.runtime_us,
+                    opts.runtime_threshold_ms,
+                    colorize,
+                )
+            )?;
+            writeln!(
+                formatter,
+                "Compile Time: {:.2}ms",
+                self.compile_us as f64 / 1000.0
+            )?;
+            writeln!(formatter, "Module Size: {}KB\n", self.size)?;
+        }
 
         Ok(())
     }
 }
 
+fn diff_json(
+    pointer: String,
+    value: &serde_json::Value,
+    other: &serde_json::Value,
+    diff: &mut Vec<String>,
+) {
+    use serde_json::Value;
+
+    match (value, other) {
+        (Value::Object(object), Value::Object(other_object)) => {
+            let mut keys: Vec<&String> = object.keys().chain(other_object.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let pointer = format!("{pointer}/{}", key.replace('~', "~0").replace('/', "~1"));
+                match (object.get(key), other_object.get(key)) {
+                    (Some(value), Some(other)) => diff_json(pointer, value, other, diff),
+                    (Some(value), None) => diff.push(format!("{pointer}: {value} != missing")),
+                    (None, Some(other)) => diff.push(format!("{pointer}: missing != {other}")),
+                    (None, None) => unreachable!(),
+                }
+            }
+        }
+        (Value::Array(array), Value::Array(other_array)) if array.len() == other_array.len() => {
+            for (index, (value, other)) in array.iter().zip(other_array).enumerate() {
+                diff_json(format!("{pointer}/{index}"), value, other, diff);
+            }
+        }
+        _ if value != other => {
+            let pointer = if pointer.is_empty() { "/" } else { &pointer };
+            diff.push(format!("{pointer}: {value} != {other}"));
+        }
+        _ => {}
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
@@ -236,26 +772,46 @@ mod tests {
 
     use super::*;
 
-    #[test]
-    fn test_js_output() -> Result<()> {
-        let mock_input_string = "{\"input_test\": \"input_value\"}".to_string();
-        let mock_function_input = serde_json::from_str(&mock_input_string)?;
-        let expected_input_display = serde_json::to_string_pretty(&mock_function_input)?;
-
-        let function_run_result = FunctionRunResult {
+    fn sample_result() -> FunctionRunResult {
+        FunctionRunResult {
             name: "test".to_string(),
             size: 100,
+            module_hash: String::new(),
             memory_usage: 1000,
-            instructions: 1001,
+            instructions: 1000,
+            max_instructions_soft: None,
+            max_instructions_hard: None,
+            runtime_us: 100,
+            compile_us: 1000,
             logs: "test".to_string(),
-            input: mock_function_input,
+            input: serde_json::json!({
+                "input_test": "input_value"
+            }),
             output: FunctionOutput::JsonOutput(serde_json::json!({
                 "test": "test"
             })),
+            output_hash: None,
+            warnings: Vec::new(),
+            deterministic: None,
+            metrics: BTreeMap::new(),
+            memory_growth_count: 0,
+            memory_growths: Vec::new(),
+            exit_code: None,
+            input_codec: None,
             profile: None,
+            memory_dump: None,
             scale_factor: 1.0,
             success: true,
+        }
+    }
+
+    #[test]
+    fn test_js_output() -> Result<()> {
+        let function_run_result = FunctionRunResult {
+            instructions: 1001,
+            ..sample_result()
         };
+        let expected_input_display = serde_json::to_string_pretty(&function_run_result.input)?;
 
         let predicate = predicates::str::contains("Instructions: 1.001K")
             .and(predicates::str::contains("Linear Memory Usage: 1000KB"))
@@ -263,31 +819,13 @@ mod tests {
             .and(predicates::str::contains("Input Size: 28B"))
             .and(predicates::str::contains("Output Size: 15B"));
         assert!(predicate.eval(&function_run_result.to_string()));
-
-        assert!(predicate.eval(&function_run_result.to_string()));
         Ok(())
     }
 
     #[test]
     fn test_js_output_1000() -> Result<()> {
-        let mock_input_string = "{\"input_test\": \"input_value\"}".to_string();
-        let mock_function_input = serde_json::from_str(&mock_input_string)?;
-        let expected_input_display = serde_json::to_string_pretty(&mock_function_input)?;
-
-        let function_run_result = FunctionRunResult {
-            name: "test".to_string(),
-            size: 100,
-            memory_usage: 1000,
-            instructions: 1000,
-            logs: "test".to_string(),
-            input: mock_function_input,
-            output: FunctionOutput::JsonOutput(serde_json::json!({
-                "test": "test"
-            })),
-            profile: None,
-            scale_factor: 1.0,
-            success: true,
-        };
+        let function_run_result = sample_result();
+        let expected_input_display = serde_json::to_string_pretty(&function_run_result.input)?;
 
         let predicate = predicates::str::contains("Instructions: 1")
             .and(predicates::str::contains("Linear Memory Usage: 1000KB"))
@@ -298,24 +836,11 @@ mod tests {
 
     #[test]
     fn test_instructions_less_than_1000() -> Result<()> {
-        let mock_input_string = "{\"input_test\": \"input_value\"}".to_string();
-        let mock_function_input = serde_json::from_str(&mock_input_string)?;
-        let expected_input_display = serde_json::to_string_pretty(&mock_function_input)?;
-
         let function_run_result = FunctionRunResult {
-            name: "test".to_string(),
-            size: 100,
-            memory_usage: 1000,
             instructions: 999,
-            logs: "test".to_string(),
-            input: mock_function_input,
-            output: FunctionOutput::JsonOutput(serde_json::json!({
-                "test": "test"
-            })),
-            profile: None,
-            scale_factor: 1.0,
-            success: true,
+            ..sample_result()
         };
+        let expected_input_display = serde_json::to_string_pretty(&function_run_result.input)?;
 
         let predicate = predicates::str::contains("Instructions: 999")
             .and(predicates::str::contains("Linear Memory Usage: 1000KB"))
@@ -323,4 +848,236 @@ mod tests {
         assert!(predicate.eval(&function_run_result.to_string()));
         Ok(())
     }
+
+    #[test]
+    fn test_render_without_input() -> Result<()> {
+        let rendered = sample_result().render(
+            &DisplayOptions {
+                show_input: false,
+                ..Default::default()
+            },
+            true,
+        );
+
+        let predicate = predicates::str::contains("Input  ")
+            .not()
+            .and(predicates::str::contains("input_value").not())
+            .and(predicates::str::contains("Output"))
+            .and(predicates::str::contains("Input Size: 28B"));
+        assert!(predicate.eval(&rendered));
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_with_display_options() -> Result<()> {
+        let function_run_result = FunctionRunResult {
+            instructions: 1_234_567,
+            ..sample_result()
+        };
+
+        let rendered = function_run_result.render(
+            &DisplayOptions {
+                show_limits: false,
+                indent: 4,
+                exact_counts: true,
+                runtime_threshold_ms: 0.05,
+                ..Default::default()
+            },
+            false,
+        );
+
+        let predicate = predicates::str::contains("Instructions: 1234567")
+            .and(predicates::str::contains("Runtime: 0.10ms"))
+            .and(predicates::str::contains(
+                "    \"input_test\": \"input_value\"",
+            ))
+            .and(predicates::str::contains("Resource Limits").not())
+            .and(predicates::str::contains("\u{1b}[").not());
+        assert!(predicate.eval(&rendered));
+        Ok(())
+    }
+
+    #[test]
+    fn test_humanize_runtime() {
+        assert_eq!(
+            humanize_runtime("Runtime", 4_500, 5.0, true),
+            "Runtime: 4.50ms"
+        );
+        assert_eq!(
+            humanize_runtime("Runtime", 4_500, 2.0, true),
+            "Runtime: 4.50ms".red().to_string()
+        );
+        assert_eq!(
+            humanize_runtime("Runtime", 4_500, 2.0, false),
+            "Runtime: 4.50ms"
+        );
+    }
+
+    #[test]
+    fn test_render_comparison() -> Result<()> {
+        let baseline = sample_result();
+        let current = FunctionRunResult {
+            instructions: 1200,
+            memory_usage: 1010,
+            ..sample_result()
+        };
+
+        let rendered = current.render_comparison(&baseline, 5.0, false);
+
+        let predicate =
+            predicates::str::contains("Instructions: 1000 -> 1200 (+20.00%) REGRESSION")
+                .and(predicates::str::contains(
+                    "Linear Memory Usage (KB): 1000 -> 1010 (+1.00%)\n",
+                ))
+                .and(predicates::str::contains(
+                    "Output Size (B): 15 -> 15 (+0.00%)",
+                ));
+        assert!(predicate.eval(&rendered));
+        Ok(())
+    }
+
+    #[test]
+    fn test_input_codec() -> Result<()> {
+        let value = serde_json::json!({"count": 5});
+        let messagepack = InputCodec::Messagepack.encode(&value)?;
+        assert_eq!(InputCodec::Messagepack.decode(&messagepack)?, value);
+        assert_eq!(InputCodec::Json.decode(b"{\"count\":5}")?, value);
+
+        let raw = [0x81, 0xa5, 0xff];
+        let echoed = InputCodec::Raw.decode(&raw)?;
+        assert_eq!(echoed, serde_json::json!("gaX/"));
+        assert_eq!(InputCodec::Raw.encode(&echoed)?, raw);
+
+        assert!(InputCodec::Json.decode(&raw).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_raw_input_size() {
+        let function_run_result = FunctionRunResult {
+            input: serde_json::json!("gaX/"),
+            input_codec: Some(InputCodec::Raw),
+            ..sample_result()
+        };
+
+        assert_eq!(function_run_result.input_size(), 3);
+    }
+
+    #[test]
+    fn test_json_accessors() {
+        let function_run_result = sample_result();
+
+        assert_eq!(
+            function_run_result.input_json(),
+            Some(&serde_json::json!({"input_test": "input_value"}))
+        );
+        assert_eq!(
+            function_run_result.output_json(),
+            Some(&serde_json::json!({"test": "test"}))
+        );
+
+        let invalid_output_result = FunctionRunResult {
+            output: FunctionOutput::InvalidJsonOutput(InvalidOutput {
+                error: "error".to_string(),
+                stdout: "not json".to_string(),
+            }),
+            ..function_run_result
+        };
+        assert_eq!(invalid_output_result.output_json(), None);
+
+        let raw_input_result = FunctionRunResult {
+            input: serde_json::json!("gaX/"),
+            input_codec: Some(InputCodec::Raw),
+            ..sample_result()
+        };
+        assert_eq!(raw_input_result.input_json(), None);
+    }
+
+    #[test]
+    fn test_redact() -> Result<()> {
+        let mut function_run_result = FunctionRunResult {
+            input: serde_json::json!({"customer": {"email": "a@example.com"}}),
+            output: FunctionOutput::JsonOutput(
+                serde_json::json!({"customer": {"email": "a@example.com"}, "ok": true}),
+            ),
+            ..sample_result()
+        };
+
+        function_run_result.redact("/customer/email")?;
+        assert!(function_run_result.redact("/missing").is_err());
+
+        assert_eq!(
+            function_run_result.input_json(),
+            Some(&serde_json::json!({"customer": {"email": "[REDACTED]"}}))
+        );
+        assert_eq!(
+            function_run_result.output_json(),
+            Some(&serde_json::json!({"customer": {"email": "[REDACTED]"}, "ok": true}))
+        );
+
+        let mut raw_input_result = FunctionRunResult {
+            input: serde_json::json!("gaX/"),
+            input_codec: Some(InputCodec::Raw),
+            ..sample_result()
+        };
+        assert!(raw_input_result.redact("/test").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_metrics() {
+        let mut function_run_result = FunctionRunResult {
+            logs: "start\nmetric:lines=42\nmetric:mode=fast\nmetric:no pair\nend".to_string(),
+            ..sample_result()
+        };
+
+        function_run_result.extract_metrics("metric:");
+
+        assert_eq!(function_run_result.logs, "start\nmetric:no pair\nend");
+        assert_eq!(
+            serde_json::to_value(&function_run_result.metrics).unwrap(),
+            serde_json::json!({"lines": 42, "mode": "fast"})
+        );
+    }
+
+    #[test]
+    fn test_output_diff() {
+        let function_run_result = |output| FunctionRunResult {
+            output,
+            ..sample_result()
+        };
+        let json_output = |value| function_run_result(FunctionOutput::JsonOutput(value));
+
+        assert_eq!(
+            json_output(serde_json::json!({"a": 1, "b": [1, 2], "c": {"d/e": true}})).output_diff(
+                &json_output(serde_json::json!({"a": 1.0, "b": [1, 3], "c": {}}))
+            ),
+            ["/a: 1 != 1.0", "/b/1: 2 != 3", "/c/d~1e: true != missing"]
+        );
+        assert!(json_output(serde_json::json!({"a": 1}))
+            .output_diff(&json_output(serde_json::json!({"a": 1})))
+            .is_empty());
+        assert_eq!(
+            json_output(serde_json::json!(1)).output_diff(&function_run_result(
+                FunctionOutput::InvalidJsonOutput(InvalidOutput {
+                    stdout: "oops".to_string(),
+                    error: "expected value".to_string(),
+                })
+            )),
+            ["/: 1 != \"oops\""]
+        );
+    }
+
+    #[test]
+    fn test_hottest_stacks() {
+        let function_run_result = FunctionRunResult {
+            profile: Some("_start;a 10\n_start;b;c 300\n_start 25\n".to_string()),
+            ..sample_result()
+        };
+
+        assert_eq!(
+            function_run_result.hottest_stacks(2),
+            vec![("_start;b;c", 300), ("_start", 25)]
+        );
+    }
 }
@@ -0,0 +1,198 @@
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use std::{fmt, str::FromStr};
+
+/// An edit applied to the input JSON before it is passed to the Function.
+///
+/// Transforms are written as an operation followed by JSON Pointers, separated by whitespace:
+/// * `remove /cart/note` removes the value at `/cart/note`
+/// * `rename /cart/lines /cart/items` moves the value at `/cart/lines` to `/cart/items`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InputTransform {
+    Remove(String),
+    Rename(String, String),
+}
+
+impl FromStr for InputTransform {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        let transform = match parts.as_slice() {
+            ["remove", pointer] => InputTransform::Remove(pointer.to_string()),
+            ["rename", from, to] => InputTransform::Rename(from.to_string(), to.to_string()),
+            _ => {
+                return Err(anyhow!(
+                "Invalid input transform `{}`, expected `remove <pointer>` or `rename <from> <to>`",
+                s
+            ))
+            }
+        };
+
+        for pointer in transform.pointers() {
+            if !pointer.starts_with('/') {
+                return Err(anyhow!(
+                    "Invalid input transform `{}`, `{}` is not a JSON Pointer",
+                    s,
+                    pointer
+                ));
+            }
+        }
+
+        Ok(transform)
+    }
+}
+
+impl fmt::Display for InputTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputTransform::Remove(pointer) => write!(f, "remove {pointer}"),
+            InputTransform::Rename(from, to) => write!(f, "rename {from} {to}"),
+        }
+    }
+}
+
+impl InputTransform {
+    fn pointers(&self) -> Vec<&str> {
+        match self {
+            InputTransform::Remove(pointer) => vec![pointer],
+            InputTransform::Rename(from, to) => vec![from, to],
+        }
+    }
+
+    /// Apply the transform to `value` in place.
+    pub fn apply(&self, value: &mut Value) -> Result<()> {
+        match self {
+            InputTransform::Remove(pointer) => {
+                remove(value, pointer)?;
+            }
+            InputTransform::Rename(from, to) => {
+                let moved = remove(value, from)?;
+                insert(value, to, moved)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Splits a JSON Pointer into its parent pointer and its unescaped last token.
+fn split_pointer(pointer: &str) -> Result<(&str, String)> {
+    let (parent, token) = pointer
+        .rsplit_once('/')
+        .ok_or_else(|| anyhow!("`{}` is not a JSON Pointer", pointer))?;
+
+    Ok((parent, token.replace("~1", "/").replace("~0", "~")))
+}
+
+fn remove(value: &mut Value, pointer: &str) -> Result<Value> {
+    let (parent, token) = split_pointer(pointer)?;
+    let removed = match value.pointer_mut(parent) {
+        Some(Value::Object(object)) => object.remove(&token),
+        Some(Value::Array(array)) => match token.parse::<usize>() {
+            Ok(index) if index < array.len() => Some(array.remove(index)),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    removed.ok_or_else(|| anyhow!("Input transform failed: no value at `{}`", pointer))
+}
+
+fn insert(value: &mut Value, pointer: &str, inserted: Value) -> Result<()> {
+    let (parent, token) = split_pointer(pointer)?;
+    match value.pointer_mut(parent) {
+        Some(Value::Object(object)) => {
+            object.insert(token, inserted);
+        }
+        Some(Value::Array(array)) if token == "-" => array.push(inserted),
+        Some(Value::Array(array)) => match token.parse::<usize>() {
+            Ok(index) if index <= array.len() => array.insert(index, inserted),
+            _ => {
+                return Err(anyhow!(
+                    "Input transform failed: `{}` is not a valid array index",
+                    pointer
+                ))
+            }
+        },
+        _ => {
+            return Err(anyhow!(
+                "Input transform failed: no object or array at `{}`",
+                parent
+            ))
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            "remove /cart/note".parse::<InputTransform>().unwrap(),
+            InputTransform::Remove("/cart/note".to_string())
+        );
+        assert_eq!(
+            "rename  /cart/lines /cart/items"
+                .parse::<InputTransform>()
+                .unwrap(),
+            InputTransform::Rename("/cart/lines".to_string(), "/cart/items".to_string())
+        );
+        assert!("delete /cart".parse::<InputTransform>().is_err());
+        assert!("remove cart".parse::<InputTransform>().is_err());
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut value = json!({"cart": {"note": "hi", "lines": [1, 2, 3]}, "a/b": 1});
+
+        for transform in ["remove /cart/note", "remove /cart/lines/1", "remove /a~1b"] {
+            transform
+                .parse::<InputTransform>()
+                .unwrap()
+                .apply(&mut value)
+                .unwrap();
+        }
+
+        assert_eq!(value, json!({"cart": {"lines": [1, 3]}}));
+    }
+
+    #[test]
+    fn test_rename() {
+        let mut value = json!({"cart": {"lines": [1, 2]}, "items": []});
+
+        "rename /cart/lines /cart/items"
+            .parse::<InputTransform>()
+            .unwrap()
+            .apply(&mut value)
+            .unwrap();
+        "rename /cart/items/0 /items/-"
+            .parse::<InputTransform>()
+            .unwrap()
+            .apply(&mut value)
+            .unwrap();
+
+        assert_eq!(value, json!({"cart": {"items": [2]}, "items": [1]}));
+    }
+
+    #[test]
+    fn test_missing_value() {
+        let mut value = json!({"cart": {}});
+
+        let error = "remove /cart/note"
+            .parse::<InputTransform>()
+            .unwrap()
+            .apply(&mut value)
+            .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Input transform failed: no value at `/cart/note`"
+        );
+    }
+}
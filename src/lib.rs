@@ -1,5 +1,9 @@
 pub mod bluejay_schema_analyzer;
 pub mod engine;
 pub mod function_run_result;
+pub mod input_transform;
 pub mod logs;
+pub mod output_assertion;
+pub mod preopen;
+pub mod run_error;
 pub mod scale_limits_analyzer;
@@ -1,20 +1,34 @@
 use core::fmt;
-use std::io;
+use std::{collections::VecDeque, io, sync::Arc};
+
+/// Receives the Function's logs as they are written, e.g. to stream them while the Function runs.
+pub trait LogSink: Send + Sync {
+    fn write_log(&self, log: &str);
+}
 
-#[derive(Debug)]
 pub struct LogStream {
-    logs: Vec<String>,
+    logs: VecDeque<String>,
     current_bytesize: usize,
+    max_bytesize: usize,
+    newlines: usize,
+    max_entries: usize,
+    sink: Option<Arc<dyn LogSink>>,
+}
+
+impl fmt::Debug for LogStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LogStream")
+            .field("logs", &self.logs)
+            .field("current_bytesize", &self.current_bytesize)
+            .field("max_bytesize", &self.max_bytesize)
+            .field("max_entries", &self.max_entries)
+            .finish_non_exhaustive()
+    }
 }
 
 impl Default for LogStream {
     fn default() -> Self {
-        let logs = Vec::new();
-        let current_bytesize = 0;
-        Self {
-            logs,
-            current_bytesize,
-        }
+        Self::with_max_bytesize(usize::MAX)
     }
 }
 
@@ -38,6 +52,42 @@ impl io::Write for LogStream {
 }
 
 impl LogStream {
+    /// Create a log stream holding at most `max_bytesize` bytes, dropping the oldest logs first.
+    ///
+    /// # Arguments
+    /// * `max_bytesize` - the byte budget of the stream
+    #[must_use]
+    pub fn with_max_bytesize(max_bytesize: usize) -> Self {
+        Self {
+            logs: VecDeque::new(),
+            current_bytesize: 0,
+            max_bytesize,
+            newlines: 0,
+            max_entries: usize::MAX,
+            sink: None,
+        }
+    }
+
+    /// Keep at most `max_entries` newline-delimited log entries, dropping the oldest first.
+    ///
+    /// # Arguments
+    /// * `max_entries` - the number of entries to keep
+    #[must_use]
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// Forward every log appended to the stream to `sink`, regardless of the byte budget and entry limit.
+    ///
+    /// # Arguments
+    /// * `sink` - the sink receiving the logs
+    #[must_use]
+    pub fn with_sink(mut self, sink: Arc<dyn LogSink>) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
     /// Append a buffer to the log stream.
     ///
     /// # Arguments
@@ -45,21 +95,92 @@ impl LogStream {
     pub fn append(&mut self, buf: &[u8]) -> usize {
         let log = String::from_utf8_lossy(buf);
 
+        if let Some(sink) = &self.sink {
+            sink.write_log(&log);
+        }
+
         let log_length = log.len();
         self.current_bytesize += log_length;
-        self.logs.push(log.into());
+        self.newlines += log.matches('\n').count();
+        self.logs.push_back(log.into());
+        self.enforce_max_bytesize();
+        self.enforce_max_entries();
 
         log_length
     }
 
+    fn enforce_max_bytesize(&mut self) {
+        while self.current_bytesize > self.max_bytesize {
+            let excess = self.current_bytesize - self.max_bytesize;
+            let Some(oldest) = self.logs.front_mut() else {
+                break;
+            };
+
+            if oldest.len() <= excess {
+                self.current_bytesize -= oldest.len();
+                self.newlines -= oldest.matches('\n').count();
+                self.logs.pop_front();
+            } else {
+                let mut cut = excess;
+                while !oldest.is_char_boundary(cut) {
+                    cut += 1;
+                }
+                self.newlines -= oldest.drain(..cut).filter(|c| *c == '\n').count();
+                self.current_bytesize -= cut;
+            }
+        }
+    }
+
+    fn enforce_max_entries(&mut self) {
+        while self.entries() > self.max_entries {
+            let Some(oldest) = self.logs.front_mut() else {
+                break;
+            };
+
+            // An entry may span several writes, drop them until its newline
+            match oldest.find('\n') {
+                Some(newline) => {
+                    oldest.drain(..=newline);
+                    self.current_bytesize -= newline + 1;
+                    self.newlines -= 1;
+                    if oldest.is_empty() {
+                        self.logs.pop_front();
+                    }
+                }
+                None => {
+                    self.current_bytesize -= oldest.len();
+                    self.logs.pop_front();
+                }
+            }
+        }
+    }
+
+    /// Number of newline-delimited entries, counting a trailing unterminated entry.
+    #[must_use]
+    pub fn entries(&self) -> usize {
+        let unterminated = self
+            .logs
+            .iter()
+            .rev()
+            .find(|log| !log.is_empty())
+            .is_some_and(|log| !log.ends_with('\n'));
+
+        self.newlines + usize::from(unterminated)
+    }
+
+    #[must_use]
+    pub fn bytesize(&self) -> usize {
+        self.current_bytesize
+    }
+
     #[must_use]
     pub fn last(&self) -> Option<&String> {
-        self.logs.last()
+        self.logs.back()
     }
 
     #[must_use]
     pub fn last_message(&self) -> Option<&str> {
-        self.logs.last().map(String::as_str)
+        self.logs.back().map(String::as_str)
     }
 }
 
@@ -85,4 +206,46 @@ mod tests {
 
         assert_eq!("helloworld", logs.to_string());
     }
+
+    #[test]
+    fn test_max_bytesize_drops_oldest_logs() {
+        let mut logs = LogStream::with_max_bytesize(10);
+
+        logs.append(b"hello");
+        logs.append(b"world");
+        assert_eq!("helloworld", logs.to_string());
+
+        logs.append(b"!!!");
+        assert_eq!("loworld!!!", logs.to_string());
+        assert_eq!(10, logs.bytesize());
+
+        logs.append(b"0123456789abc");
+        assert_eq!("3456789abc", logs.to_string());
+        assert_eq!(10, logs.bytesize());
+    }
+
+    #[test]
+    fn test_max_bytesize_keeps_char_boundaries() {
+        let mut logs = LogStream::with_max_bytesize(4);
+
+        logs.append("☠☠".as_bytes());
+        assert_eq!("☠", logs.to_string());
+        assert_eq!(3, logs.bytesize());
+    }
+
+    #[test]
+    fn test_max_entries_drops_oldest_entries() {
+        let mut logs = LogStream::default().with_max_entries(2);
+
+        logs.append(b"one\ntw");
+        logs.append(b"o\nthree\n");
+        assert_eq!("two\nthree\n", logs.to_string());
+        assert_eq!(2, logs.entries());
+        assert_eq!(10, logs.bytesize());
+
+        logs.append(b"fo");
+        logs.append(b"ur");
+        assert_eq!("three\nfour", logs.to_string());
+        assert_eq!(2, logs.entries());
+    }
 }
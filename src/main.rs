@@ -1,20 +1,36 @@
 use std::{
-    fs::File,
-    io::{stdin, BufReader, Read},
-    path::PathBuf,
+    collections::BTreeMap,
+    ffi::OsString,
+    fmt,
+    fs::{File, OpenOptions},
+    io::{stdin, BufReader, Cursor, Read, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use anyhow::{anyhow, Result};
-use clap::{Parser, ValueEnum};
+use base64::{prelude::BASE64_STANDARD, Engine};
+use clap::{parser::ValueSource, Arg, ArgGroup, CommandFactory, Parser, ValueEnum};
 use function_runner::{
     bluejay_schema_analyzer::BluejaySchemaAnalyzer,
-    engine::{run, FunctionRunParams, ProfileOpts},
+    engine::{
+        function_exports, measure_compile, run, standard_providers, FunctionRunParams, ProfileOpts,
+        WASMTIME_VERSION,
+    },
+    function_run_result::{DisplayOptions, FunctionOutput, FunctionRunResult, InputCodec},
+    input_transform::InputTransform,
+    output_assertion::OutputAssertion,
+    preopen::Preopen,
+    scale_limits_analyzer::{ScaleLimits, SelectedScaleLimit},
 };
 
 use is_terminal::IsTerminal;
+use serde::Deserialize;
+use serde_json_path::{ExactlyOneError, JsonPath};
 
 const PROFILE_DEFAULT_INTERVAL: u32 = 500_000; // every 5us
 const DEFAULT_SCALE_FACTOR: f64 = 1.0;
+const DEFAULT_CONFIG_PATH: &str = "function-runner.toml";
 
 /// Supported input flavors
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
@@ -25,13 +41,111 @@ enum Codec {
     Raw,
     /// JSON input, will be converted to MessagePack, must be valid JSON
     JsonToMessagepack,
+    /// MessagePack input, passed as-is, must be valid MessagePack
+    Messagepack,
+    /// Detect the codec from the input: JSON if it parses as JSON, MessagePack if it decodes to a
+    /// MessagePack map, raw otherwise, including top-level MessagePack arrays and scalars
+    Auto,
+}
+
+impl Codec {
+    /// Detects the codec of `input`, see [`Codec::Auto`].
+    fn detect(input: &[u8]) -> Self {
+        if serde_json::from_slice::<serde::de::IgnoredAny>(input).is_ok() {
+            Codec::Json
+        } else if matches!(
+            InputCodec::Messagepack.decode(input),
+            Ok(serde_json::Value::Object(_))
+        ) {
+            Codec::Messagepack
+        } else {
+            Codec::Raw
+        }
+    }
+
+    /// The codec passing input encoded with `input_codec` as-is.
+    fn from_input_codec(input_codec: InputCodec) -> Self {
+        match input_codec {
+            InputCodec::Json => Codec::Json,
+            InputCodec::Messagepack => Codec::Messagepack,
+            InputCodec::Raw => Codec::Raw,
+        }
+    }
+
+    /// The encoding of the bytes the Function receives with this codec.
+    fn input_codec(self) -> InputCodec {
+        match self {
+            Codec::Json => InputCodec::Json,
+            Codec::Raw => InputCodec::Raw,
+            Codec::JsonToMessagepack | Codec::Messagepack => InputCodec::Messagepack,
+            Codec::Auto => unreachable!("the codec is detected before running"),
+        }
+    }
+}
+
+/// Supported encodings for printing binary data
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum Encoding {
+    /// Lowercase hexadecimal
+    Hex,
+    /// Standard base64
+    Base64,
+}
+
+/// Supported compression formats for input and profile files
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum Compression {
+    /// Uncompressed
+    None,
+    /// Zstandard
+    Zstd,
+}
+
+impl Compression {
+    /// Infers the compression from a file extension, `.zst` being Zstandard.
+    fn from_path(path: Option<&PathBuf>) -> Self {
+        match path.and_then(|path| path.extension()) {
+            Some(extension) if extension == "zst" => Compression::Zstd,
+            _ => Compression::None,
+        }
+    }
 }
 
 /// Simple Function runner which takes JSON as a convenience.
 #[derive(Parser, Debug)]
 #[clap(version)]
-#[command(arg_required_else_help = true)]
+#[command(
+    arg_required_else_help = true,
+    group(
+        ArgGroup::new("profiling")
+            .multiple(true)
+            .args(["profile", "profile_out", "profile_frequency", "profile_top"])
+    ),
+    group(
+        ArgGroup::new("display")
+            .multiple(true)
+            .args(["no_input_echo", "no_limits", "indent", "exact_counts", "runtime_threshold"])
+    )
+)]
 struct Opts {
+    /// Path to a TOML file with default values for any flag, keyed by flag name, e.g. `codec = "raw"`.
+    /// Defaults to `function-runner.toml` in the current directory, if it exists. Flags passed on the
+    /// command line take precedence.
+    #[clap(long)]
+    config: Option<PathBuf>,
+
+    /// Print the runner, wasmtime and embedded provider versions, then exit.
+    #[clap(long)]
+    version_full: bool,
+
+    /// Print the Function in the WebAssembly text format, then exit without running it.
+    #[clap(long)]
+    show_wat: bool,
+
+    /// Compile the Function this many times without running it and print the compile time percentiles, then exit.
+    #[clap(long)]
+    measure_compile_only: Option<usize>,
+
     /// Path to wasm/wat Function
     #[clap(short, long, default_value = "function.wasm")]
     function: PathBuf,
@@ -40,14 +154,115 @@ struct Opts {
     #[clap(short, long)]
     input: Option<PathBuf>,
 
+    /// Input bytes encoded as standard base64, e.g. for binary inputs with `--codec raw`.
+    #[clap(long, conflicts_with_all = ["input", "payload"])]
+    input_base64: Option<String>,
+
+    /// Path to a run result previously saved with --json; runs the Function with that run's input,
+    /// encoded with that run's codec, e.g. to reproduce a reported issue.
+    #[clap(long, conflicts_with_all = ["input", "payload", "input_base64", "input_fd", "codec"])]
+    replay: Option<PathBuf>,
+
+    /// Inherited file descriptor to read the input from, e.g. a pipe opened by a test harness, or 0 for stdin.
+    /// Unix only.
+    #[clap(long, conflicts_with_all = ["input", "payload", "input_base64"])]
+    input_fd: Option<u32>,
+
+    /// Path to a json file containing an `{"input": ..., "configuration": ...}` envelope, passed to the Function as-is.
+    #[clap(long, conflicts_with = "input")]
+    payload: Option<PathBuf>,
+
     /// Name of the export to invoke.
     #[clap(short, long, default_value = "_start")]
     export: String,
 
+    /// Position of the export to invoke in the module's export list, as an alternative to --export.
+    #[clap(long, conflicts_with = "export")]
+    export_index: Option<usize>,
+
+    /// Run every exported `() -> ()` function in turn, each in a fresh instance with the same input, and report
+    /// them together. Exports with other signatures are skipped.
+    #[clap(long, conflicts_with_all = [
+        "export", "export_index", "assertions", "redact", "output_hash", "verify_determinism", "compare_baseline",
+        "benchmark_json", "max_instructions_per_input_byte", "trace_memory", "profiling", "dump_on_trap",
+    ])]
+    export_all: bool,
+
+    /// Print the instructions each export used in an --export-all run, and their share of the total.
+    #[clap(long, requires = "export_all")]
+    trace_fuel_per_export: bool,
+
     /// Log the run result as a JSON object
-    #[clap(short, long)]
+    #[clap(short, long, conflicts_with = "display")]
     json: bool,
 
+    /// Log the run result as a single-line JSON object, e.g. for newline-delimited log ingestion.
+    #[clap(long, conflicts_with = "display")]
+    json_compact: bool,
+
+    /// Check the output JSON after the run, as `<pointer>` for existence or `<pointer><op><json>` with `==` or
+    /// `!=`, e.g. `/cart`, `/cart/total==42` or `/status!="ok"`. Numbers are compared by value.
+    /// May be repeated; the run fails if any assertion fails.
+    #[clap(long = "assert")]
+    assertions: Vec<OutputAssertion>,
+
+    /// Replace the value at this JSON Pointer in the displayed input and output with "[REDACTED]",
+    /// e.g. to share a run result without PII. Fails when the pointer matches neither, or with a raw input.
+    /// May be repeated.
+    #[clap(long)]
+    redact: Vec<String>,
+
+    /// Print only the Function output: the output JSON, or the raw stdout when it isn't valid JSON.
+    #[clap(long, conflicts_with_all = ["json", "json_compact", "display"])]
+    output_only: bool,
+
+    /// Omit the Input section from the run result.
+    #[clap(long)]
+    no_input_echo: bool,
+
+    /// Omit the Resource Limits section from the run result.
+    #[clap(long)]
+    no_limits: bool,
+
+    /// Disable colors in the run result.
+    #[clap(long, conflicts_with = "compare_codec")]
+    no_color: bool,
+
+    /// Number of spaces used to indent the input and output JSON in the run result.
+    #[clap(long, default_value = "2")]
+    indent: usize,
+
+    /// Print exact instruction counts instead of rounding them.
+    #[clap(long)]
+    exact_counts: bool,
+
+    /// Number of instructions above which the run result includes a warning; the run still succeeds.
+    #[clap(long)]
+    max_instructions_soft: Option<u64>,
+
+    /// Number of instructions after which the Function is stopped and the run fails.
+    #[clap(long)]
+    max_instructions_hard: Option<u64>,
+
+    /// Fail if the Function executes more instructions per byte of input than this, e.g. to catch
+    /// accidentally quadratic behavior that only shows on large inputs.
+    #[clap(long)]
+    max_instructions_per_input_byte: Option<f64>,
+
+    /// Stop the Function once it executed this many instructions, without failing the run, e.g. to bisect
+    /// where its behavior changes. Combine with --dump-on-trap to save its memory at that point.
+    #[clap(long, conflicts_with = "max_instructions_hard")]
+    run_until_instructions: Option<u64>,
+
+    /// Stop the Function and fail the run once it ran for this many milliseconds, e.g. to catch an infinite loop
+    /// without waiting for the instruction limit.
+    #[clap(long, conflicts_with = "profiling")]
+    timeout: Option<u64>,
+
+    /// Runtime in milliseconds above which the runtime is shown in red.
+    #[clap(long, default_value = "5.0")]
+    runtime_threshold: f64,
+
     /// Enable profiling. This will make your Function run slower.
     /// The resulting profile can be used in speedscope (https://www.speedscope.app/)
     /// Specifying --profile-* argument will also enable profiling.
@@ -62,9 +277,36 @@ struct Opts {
     #[clap(long)]
     profile_frequency: Option<u32>,
 
+    /// Compression of the input; if omitted, inferred from the --input extension (`.zst` is zstd).
+    #[clap(long, value_enum)]
+    input_compression: Option<Compression>,
+
+    /// Print the given number of hottest stacks of the profile after the run, by sample weight.
+    #[clap(long)]
+    profile_top: Option<usize>,
+
+    /// Compression of the saved profile; if omitted, inferred from the --profile-out extension (`.zst` is zstd).
+    #[clap(long, value_enum, requires = "profiling")]
+    profile_compression: Option<Compression>,
+
     #[clap(short = 'c', long, value_enum, default_value = "json")]
     codec: Codec,
 
+    /// Print the input bytes the Function would receive, after transforms and --codec encoding, then exit.
+    #[clap(long, value_enum)]
+    print_input_encoded: Option<Encoding>,
+
+    /// Use the value at this JSONPath of the input as the Function's input, e.g. `$.data.functionInput`
+    /// to unwrap a captured request body. Fails unless it matches exactly one value. Applied before
+    /// --input-transform.
+    #[clap(long)]
+    input_jsonpath: Option<JsonPath>,
+
+    /// Edit the input JSON before running the Function, e.g. `remove /cart/note` or
+    /// `rename /cart/lines /cart/items` (JSON Pointers). May be repeated; applied in order.
+    #[clap(long)]
+    input_transform: Vec<InputTransform>,
+
     /// Path to graphql file containing Function schema; if omitted, defaults will be used to calculate limits.
     #[clap(short = 's', long)]
     schema_path: Option<PathBuf>,
@@ -72,11 +314,124 @@ struct Opts {
     /// Path to graphql file containing Function input query; if omitted, defaults will be used to calculate limits.
     #[clap(short = 'q', long)]
     query_path: Option<PathBuf>,
+
+    /// Path to a graphql file containing both the Function schema and input query, as an alternative to
+    /// --schema-path and --query-path.
+    #[clap(long, conflicts_with_all = ["schema_path", "query_path"])]
+    graphql_document: Option<PathBuf>,
+
+    /// Without --query-path, compute the scale factor as if every @scaleLimits field in the schema were selected.
+    /// This over-estimates the limits compared to the Function's real query.
+    #[clap(long, conflicts_with = "query_path", requires = "schema_path")]
+    assume_full_query: bool,
+
+    /// List the @scaleLimits fields selected by --query-path, then exit without running the Function.
+    #[clap(long, requires_all = ["schema_path", "query_path"])]
+    show_selected_limits: bool,
+
+    /// Write the Function's linear memory to this file if it traps, for post-mortem inspection.
+    /// Dumps are capped at 256MiB.
+    #[clap(long)]
+    dump_on_trap: Option<PathBuf>,
+
+    /// Grow the Function's memory to this many 64KiB pages before invoking it, to leave growth out of benchmarks.
+    /// The reported memory usage and growths exclude the preallocation.
+    #[clap(long)]
+    preallocate_memory: Option<u64>,
+
+    /// Treat log lines starting with this prefix as `key=value` metrics, e.g. `metric:` for `metric:lines=42`.
+    /// They are removed from the logs and reported under `metrics` in the run result.
+    #[clap(long)]
+    metric_prefix: Option<String>,
+
+    /// Print every memory growth of the Function as a from/to page count, e.g. to spot many small growths.
+    #[clap(long)]
+    trace_memory: bool,
+
+    /// Stop the Function as soon as it writes to stderr, reporting its first log and the instruction count at
+    /// that point, e.g. to find where a Function that logs before failing goes wrong.
+    #[clap(long, conflicts_with_all = ["profile", "profile_out", "profile_frequency", "profile_top"])]
+    abort_on_first_log: bool,
+
+    /// Keep at most this many newline-delimited log entries, dropping the oldest, to mirror entry-based truncation.
+    #[clap(long)]
+    max_log_entries: Option<usize>,
+
+    /// Maximum size of the wasm stack in bytes, for legitimately stack-heavy Functions. Defaults to 512KiB.
+    #[clap(long)]
+    max_wasm_stack: Option<usize>,
+
+    /// Link imported functions that the runner doesn't provide to stubs that trap when called,
+    /// to test the code paths of a partially-built Function that don't use them.
+    #[clap(long)]
+    stub_unknown_imports: bool,
+
+    /// Print every call the Function makes to its provider's imports, with their arguments, to stderr.
+    /// WASI imports aren't traced.
+    #[clap(long)]
+    trace_imports: bool,
+
+    /// Make a host directory readable by the Function under a guest path, as `<guest-path>=<host-path>`.
+    /// Can be repeated. The directories are read-only, but their contents can still change between runs,
+    /// which makes the Function's results less reproducible.
+    #[clap(long = "preopen")]
+    preopens: Vec<Preopen>,
+
+    /// Comma-separated list of the functions the module may export, e.g. `run,_start`. Fails if it exports
+    /// any other function; memory, table and global exports are ignored.
+    #[clap(long, value_delimiter = ',')]
+    expected_exports: Option<Vec<String>>,
+
+    /// Fail if the Function imports WASI, e.g. when it should be built for wasm32-unknown-unknown.
+    #[clap(long)]
+    forbid_wasi: bool,
+
+    /// Run the Function a second time with the same input and fail if its output or instruction count differ.
+    #[clap(long)]
+    verify_determinism: bool,
+
+    /// Run the input through both the JSON and MessagePack codecs and fail if the outputs differ, or if the
+    /// instruction counts differ by more than --codec-tolerance percent.
+    #[clap(long, conflicts_with_all = [
+        "export_all", "verify_determinism", "json", "json_compact", "output_only", "assertions", "redact",
+        "output_hash", "compare_baseline", "benchmark_json", "max_instructions_per_input_byte", "trace_memory",
+        "profiling", "dump_on_trap", "metric_prefix", "display",
+    ])]
+    compare_codec: bool,
+
+    /// Percentage by which the instruction counts of the --compare-codec runs may differ.
+    #[clap(long, default_value = "10.0", requires = "compare_codec")]
+    codec_tolerance: f64,
+
+    /// Include a SHA-256 of the canonical (minified, sorted keys) output JSON in the run result.
+    #[clap(long)]
+    output_hash: bool,
+
+    /// Path to a run result previously saved with --json; prints how this run's metrics changed relative to it.
+    #[clap(long)]
+    compare_baseline: Option<PathBuf>,
+
+    /// Percentage increase over the --compare-baseline metrics that is flagged as a regression.
+    #[clap(long, default_value = "5.0", requires = "compare_baseline")]
+    regression_threshold: f64,
+
+    /// Append a JSON line with the benchmark results of this run to the given file.
+    /// Over many runs this builds a history that can be plotted.
+    #[clap(long)]
+    benchmark_json: Option<PathBuf>,
+
+    /// Label recorded as the commit in --benchmark-json entries; defaults to the GIT_COMMIT environment variable.
+    #[clap(long, requires = "benchmark_json")]
+    label: Option<String>,
 }
 
 impl Opts {
     pub fn profile_opts(&self) -> Option<ProfileOpts> {
-        if !self.profile && self.profile_out.is_none() && self.profile_frequency.is_none() {
+        if !self.profile
+            && self.profile_out.is_none()
+            && self.profile_frequency.is_none()
+            && self.profile_top.is_none()
+        {
             return None;
         }
 
@@ -89,6 +444,11 @@ impl Opts {
         Some(ProfileOpts { interval, out })
     }
 
+    /// Whether stdout is reserved for a machine-readable result, other reports then go to stderr.
+    fn machine_readable(&self) -> bool {
+        self.json || self.json_compact || self.output_only
+    }
+
     fn default_profile_out(&self) -> PathBuf {
         let mut path = PathBuf::new();
 
@@ -102,6 +462,56 @@ impl Opts {
         path
     }
 
+    fn function_run_params<'a>(
+        &'a self,
+        input: Vec<u8>,
+        input_codec: InputCodec,
+        input_json: Option<serde_json::Value>,
+        scale_factor: f64,
+        profile_opts: Option<&'a ProfileOpts>,
+    ) -> FunctionRunParams<'a> {
+        FunctionRunParams {
+            function_path: self.function.clone(),
+            input,
+            input_codec,
+            input_json,
+            export: self.export.as_ref(),
+            export_index: self.export_index,
+            profile_opts,
+            scale_factor,
+            forbid_wasi: self.forbid_wasi,
+            dump_memory_on_trap: self.dump_on_trap.is_some(),
+            preallocate_memory_pages: self.preallocate_memory,
+            max_wasm_stack: self.max_wasm_stack,
+            log_sink: None,
+            stub_unknown_imports: self.stub_unknown_imports,
+            trace_imports: self.trace_imports,
+            max_instructions: self.max_instructions_hard,
+            max_instructions_soft: self.max_instructions_soft,
+            max_log_entries: self.max_log_entries,
+            abort_on_first_log: self.abort_on_first_log,
+            preopens: &self.preopens,
+            run_until_instructions: self.run_until_instructions,
+            timeout: self.timeout.map(Duration::from_millis),
+        }
+    }
+
+    pub fn display_opts(&self) -> DisplayOptions {
+        DisplayOptions {
+            show_input: !self.no_input_echo,
+            show_limits: !self.no_limits,
+            indent: self.indent,
+            exact_counts: self.exact_counts,
+            runtime_threshold_ms: self.runtime_threshold,
+            ..Default::default()
+        }
+    }
+
+    /// Whether the run result should use terminal colors, honouring --no-color and the terminal's support.
+    pub fn colorize(&self) -> bool {
+        !self.no_color && colored::control::SHOULD_COLORIZE.should_colorize()
+    }
+
     pub fn read_schema_to_string(&self) -> Option<Result<String>> {
         self.schema_path.as_ref().map(read_file_to_string)
     }
@@ -122,83 +532,906 @@ fn read_file_to_string(file_path: &PathBuf) -> Result<String> {
     Ok(contents)
 }
 
-fn main() -> Result<()> {
-    let opts: Opts = Opts::parse();
-
-    let mut input: Box<dyn Read + Sync + Send + 'static> = if let Some(ref input) = opts.input {
-        Box::new(BufReader::new(File::open(input).map_err(|e| {
-            anyhow!("Couldn't load input {:?}: {}", input, e)
-        })?))
-    } else if !std::io::stdin().is_terminal() {
-        Box::new(BufReader::new(stdin()))
+fn read_baseline(file_path: &PathBuf) -> Result<FunctionRunResult> {
+    let contents = read_file_to_string(file_path)?;
+    serde_json::from_str(&contents)
+        .map_err(|e| anyhow!("Invalid baseline {}: {}", file_path.to_string_lossy(), e))
+}
+
+/// The input bytes of a saved run result, re-encoded with the codec it was run with, along with that codec.
+/// Results saved before the codec was recorded had JSON inputs.
+fn read_replay_input(file_path: &PathBuf) -> Result<(Vec<u8>, InputCodec)> {
+    let contents = read_file_to_string(file_path)?;
+    let function_run_result: FunctionRunResult = serde_json::from_str(&contents)
+        .map_err(|e| anyhow!("Invalid run result {}: {}", file_path.to_string_lossy(), e))?;
+
+    let input_codec = function_run_result.input_codec.unwrap_or_default();
+    Ok((input_codec.encode(&function_run_result.input)?, input_codec))
+}
+
+fn validate_payload(buffer: &[u8]) -> Result<()> {
+    let payload = serde_json::from_slice::<serde_json::Value>(buffer)
+        .map_err(|e| anyhow!("Invalid payload JSON: {}", e))?;
+
+    let payload = payload.as_object().ok_or_else(|| {
+        anyhow!("Invalid payload: expected an object with `input` and `configuration` keys")
+    })?;
+
+    for key in ["input", "configuration"] {
+        if !payload.contains_key(key) {
+            return Err(anyhow!("Invalid payload: missing `{}` key", key));
+        }
+    }
+
+    Ok(())
+}
+
+fn append_benchmark_json(
+    file_path: &PathBuf,
+    label: Option<String>,
+    function_run_result: &FunctionRunResult,
+) -> Result<()> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let commit = label.or_else(|| std::env::var("GIT_COMMIT").ok());
+
+    let entry = serde_json::json!({
+        "timestamp": timestamp,
+        "commit": commit,
+        "name": function_run_result.name,
+        "instructions": function_run_result.instructions,
+        "memory_usage": function_run_result.memory_usage,
+        "runtime_us": function_run_result.runtime_us,
+        "compile_us": function_run_result.compile_us,
+    });
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(file_path)
+        .map_err(|e| anyhow!("Couldn't open file {}: {}", file_path.to_string_lossy(), e))?;
+
+    writeln!(file, "{entry}")
+        .map_err(|e| anyhow!("Couldn't write file {}: {}", file_path.to_string_lossy(), e))?;
+
+    Ok(())
+}
+
+/// The single value matched by `jsonpath` in the input, e.g. to unwrap a captured request body.
+fn select_input(json: &serde_json::Value, jsonpath: &JsonPath) -> Result<serde_json::Value> {
+    match jsonpath.query(json).exactly_one() {
+        Ok(value) => Ok(value.clone()),
+        Err(ExactlyOneError::Empty) => Err(anyhow!(
+            "JSONPath `{}` matches no value in the input",
+            jsonpath
+        )),
+        Err(ExactlyOneError::MoreThanOne(count)) => Err(anyhow!(
+            "JSONPath `{}` matches {} values in the input, expected one",
+            jsonpath,
+            count
+        )),
+    }
+}
+
+fn apply_input_transforms(
+    transforms: &[InputTransform],
+    json: &mut serde_json::Value,
+) -> Result<()> {
+    for transform in transforms {
+        transform
+            .apply(json)
+            .map_err(|e| anyhow!("Couldn't apply `{}`: {}", transform, e))?;
+    }
+    Ok(())
+}
+
+fn check_expected_exports(function_path: &Path, expected_exports: &[String]) -> Result<()> {
+    let unexpected_exports: Vec<String> = function_exports(function_path)?
+        .into_iter()
+        .map(|(export, _)| export)
+        .filter(|export| !expected_exports.contains(export))
+        .map(|export| format!("`{export}`"))
+        .collect();
+
+    if unexpected_exports.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "The Function exports unexpected functions: {}",
+            unexpected_exports.join(", ")
+        ))
+    }
+}
+
+/// Runs each invocable export of the Function and prints the results, keyed by export name under --json.
+fn run_all_exports(
+    opts: &Opts,
+    input: Vec<u8>,
+    input_codec: InputCodec,
+    input_json: Option<serde_json::Value>,
+    scale_factor: f64,
+    display_opts: &DisplayOptions,
+) -> Result<()> {
+    let machine_readable = opts.machine_readable();
+    let mut results = serde_json::Map::new();
+    let mut failed_exports = Vec::new();
+    let mut fuel_per_export = Vec::new();
+
+    for (export, signature) in function_exports(&opts.function)? {
+        if let Err(error) = signature {
+            eprintln!("Skipping `{export}`: {error}");
+            continue;
+        }
+
+        let mut function_run_result = run(FunctionRunParams {
+            export: &export,
+            export_index: None,
+            ..opts.function_run_params(
+                input.clone(),
+                input_codec,
+                input_json.clone(),
+                scale_factor,
+                None,
+            )
+        })?;
+
+        if let Some(metric_prefix) = opts.metric_prefix.as_deref() {
+            function_run_result.extract_metrics(metric_prefix);
+        }
+
+        if !function_run_result.success {
+            failed_exports.push(export.clone());
+        }
+        fuel_per_export.push((export.clone(), function_run_result.instructions));
+
+        if opts.output_only {
+            let output = match function_run_result.output {
+                FunctionOutput::JsonOutput(output) => output,
+                FunctionOutput::InvalidJsonOutput(invalid_output) => {
+                    serde_json::Value::String(invalid_output.stdout)
+                }
+            };
+            results.insert(export, output);
+        } else if machine_readable {
+            results.insert(
+                export,
+                serde_json::to_value(&function_run_result)
+                    .map_err(|e| anyhow!("Couldn't serialize JSON: {}", e))?,
+            );
+        } else {
+            println!("Export: {export}");
+            println!(
+                "{}",
+                function_run_result.render(display_opts, opts.colorize())
+            );
+        }
+    }
+
+    if machine_readable {
+        let results = if opts.json_compact {
+            serde_json::to_string(&results)
+        } else {
+            serde_json::to_string_pretty(&results)
+        };
+        println!(
+            "{}",
+            results.map_err(|e| anyhow!("Couldn't serialize JSON: {}", e))?
+        );
+    }
+
+    if opts.trace_fuel_per_export {
+        let total_fuel: u64 = fuel_per_export.iter().map(|(_, fuel)| fuel).sum();
+        let fuel_per_export: Vec<String> = fuel_per_export
+            .iter()
+            .map(|(export, fuel)| {
+                let share = *fuel as f64 / total_fuel.max(1) as f64 * 100.0;
+                format!("{export}: {fuel} ({share:.2}%)")
+            })
+            .collect();
+        let fuel_per_export = fuel_per_export.join("\n");
+        if machine_readable {
+            eprintln!("{fuel_per_export}");
+        } else {
+            println!("Fuel per export:\n{fuel_per_export}\n");
+        }
+    }
+
+    if failed_exports.is_empty() {
+        Ok(())
     } else {
+        anyhow::bail!(
+            "The Function execution failed for {}. Review the logs for more information.",
+            failed_exports
+                .iter()
+                .map(|export| format!("`{export}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+/// Runs the Function with the input encoded as JSON and as MessagePack and checks the runs are equivalent.
+fn compare_codecs(opts: &Opts, json_value: &serde_json::Value, scale_factor: f64) -> Result<()> {
+    let json_input =
+        serde_json::to_vec(json_value).map_err(|e| anyhow!("Couldn't serialize JSON: {}", e))?;
+    let messagepack_input = rmp_serde::to_vec(json_value)
+        .map_err(|e| anyhow!("Couldn't convert JSON to MessagePack: {}", e))?;
+
+    let json_run_result = run(opts.function_run_params(
+        json_input,
+        InputCodec::Json,
+        Some(json_value.clone()),
+        scale_factor,
+        None,
+    ))?;
+    let messagepack_run_result = run(opts.function_run_params(
+        messagepack_input,
+        InputCodec::Messagepack,
+        Some(json_value.clone()),
+        scale_factor,
+        None,
+    ))?;
+
+    let instructions_delta = (messagepack_run_result.instructions as f64
+        - json_run_result.instructions as f64)
+        / (json_run_result.instructions as f64).max(1.0)
+        * 100.0;
+    println!("JSON instructions: {}", json_run_result.instructions);
+    println!(
+        "MessagePack instructions: {} ({:+.2}%)",
+        messagepack_run_result.instructions, instructions_delta
+    );
+
+    let output_diff = json_run_result.output_diff(&messagepack_run_result);
+    if !output_diff.is_empty() {
+        println!("Outputs differ (JSON != MessagePack):");
+        for line in &output_diff {
+            println!("  {line}");
+        }
+    }
+
+    for (codec, run_result) in [
+        ("JSON", &json_run_result),
+        ("MessagePack", &messagepack_run_result),
+    ] {
+        if !run_result.success {
+            println!("{codec} run logs:\n{}", run_result.logs);
+        }
+    }
+
+    if !json_run_result.success || !messagepack_run_result.success {
+        anyhow::bail!("The Function execution failed. Review the logs for more information.")
+    }
+    if !output_diff.is_empty() {
+        anyhow::bail!("The Function's output depends on the input codec")
+    }
+    if instructions_delta.abs() > opts.codec_tolerance {
+        anyhow::bail!(
+            "The Function's instruction counts differ by more than {}% between codecs",
+            opts.codec_tolerance
+        )
+    }
+
+    Ok(())
+}
+
+fn print_selected_limits(opts: &Opts) -> Result<()> {
+    let schema_string = opts
+        .read_schema_to_string()
+        .transpose()?
+        .unwrap_or_default();
+    let query_string = opts.read_query_to_string().transpose()?.unwrap_or_default();
+
+    let selected = BluejaySchemaAnalyzer::selected_scale_limits(
+        &schema_string,
+        opts.schema_path.as_ref().and_then(|p| p.to_str()),
+        &query_string,
+        opts.query_path.as_ref().and_then(|p| p.to_str()),
+    )?;
+
+    if selected.is_empty() {
+        println!("The query selects no @scaleLimits fields");
+    }
+    for SelectedScaleLimit { path, rate } in selected {
+        println!("{path} (rate: {rate})");
+    }
+
+    Ok(())
+}
+
+/// A value of the config file, for a flag, an option or a repeatable option.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ConfigValue {
+    Flag(bool),
+    Value(ConfigScalar),
+    Values(Vec<ConfigScalar>),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ConfigScalar {
+    String(String),
+    Integer(i64),
+    Float(f64),
+}
+
+impl fmt::Display for ConfigScalar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::String(value) => write!(f, "{value}"),
+            Self::Integer(value) => write!(f, "{value}"),
+            Self::Float(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+/// Merges the flags from the config file into the command line arguments. A flag from the config file is only used
+/// when neither it nor a flag conflicting with it is on the command line.
+fn args_with_config(args: Vec<OsString>) -> Result<Vec<OsString>> {
+    let command = Opts::command();
+    // Errors are left to the actual parsing, which will report them
+    let Ok(cli_matches) = command
+        .clone()
+        .arg_required_else_help(false)
+        .ignore_errors(true)
+        .try_get_matches_from(&args)
+    else {
+        return Ok(args);
+    };
+    let config_path = match cli_matches.get_one::<PathBuf>("config") {
+        Some(path) => path.clone(),
+        None if Path::new(DEFAULT_CONFIG_PATH).exists() => PathBuf::from(DEFAULT_CONFIG_PATH),
+        None => return Ok(args),
+    };
+
+    let contents = std::fs::read_to_string(&config_path)
+        .map_err(|e| anyhow!("Couldn't load config {:?}: {}", config_path, e))?;
+    let config: BTreeMap<String, ConfigValue> = toml::from_str(&contents)
+        .map_err(|e| anyhow!("Invalid config {:?}: {}", config_path, e))?;
+
+    let cli_args: Vec<&Arg> = command
+        .get_arguments()
+        .filter(|arg| {
+            cli_matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine)
+        })
+        .collect();
+
+    let mut config_args = Vec::new();
+    for (key, value) in config {
+        let long = key.replace('_', "-");
+        let arg = command
+            .get_arguments()
+            .find(|arg| arg.get_long() == Some(long.as_str()))
+            .ok_or_else(|| anyhow!("Invalid config {:?}: unknown flag `{}`", config_path, key))?;
+
+        let overridden = cli_args.iter().any(|cli_arg| {
+            cli_arg.get_id() == arg.get_id()
+                || command
+                    .get_arg_conflicts_with(arg)
+                    .iter()
+                    .any(|conflict| conflict.get_id() == cli_arg.get_id())
+                || command
+                    .get_arg_conflicts_with(cli_arg)
+                    .iter()
+                    .any(|conflict| conflict.get_id() == arg.get_id())
+        });
+        if overridden {
+            continue;
+        }
+
+        let flag = format!("--{long}");
+        match value {
+            ConfigValue::Flag(true) => config_args.push(flag.into()),
+            ConfigValue::Flag(false) => {}
+            ConfigValue::Value(value) => {
+                config_args.extend([flag.into(), value.to_string().into()])
+            }
+            ConfigValue::Values(values) => {
+                for value in values {
+                    config_args.extend([flag.clone().into(), value.to_string().into()]);
+                }
+            }
+        }
+    }
+
+    let mut args = args.into_iter();
+    Ok(args
+        .next()
+        .into_iter()
+        .chain(config_args)
+        .chain(args)
+        .collect())
+}
+
+/// Takes ownership of an inherited file descriptor, other than the standard streams, once it is checked to be open.
+#[cfg(unix)]
+fn file_from_fd(fd: u32) -> Result<File> {
+    use std::os::fd::{FromRawFd, RawFd};
+
+    if fd <= 2 {
+        return Err(anyhow!(
+            "Invalid --input-fd {}: only stdin (0) and descriptors above 2 can be read",
+            fd
+        ));
+    }
+    let raw_fd =
+        RawFd::try_from(fd).map_err(|_| anyhow!("Invalid --input-fd {}: out of range", fd))?;
+
+    // SAFETY: fcntl only reads the descriptor's flags, and fails for descriptors that aren't open.
+    if unsafe { libc::fcntl(raw_fd, libc::F_GETFD) } == -1 {
+        return Err(anyhow!(
+            "Invalid --input-fd {}: {}",
+            fd,
+            std::io::Error::last_os_error()
+        ));
+    }
+
+    // SAFETY: the descriptor is open, isn't one of the standard streams, and is otherwise unused by the runner,
+    // so the returned file is its only owner.
+    Ok(unsafe { File::from_raw_fd(raw_fd) })
+}
+
+#[cfg(not(unix))]
+fn file_from_fd(_fd: u32) -> Result<File> {
+    Err(anyhow!("--input-fd is only supported on Unix"))
+}
+
+fn print_compile_times(function_path: &Path, iterations: usize) -> Result<()> {
+    if iterations == 0 {
         return Err(anyhow!(
-            "You must provide input via the --input flag or piped via stdin."
+            "--measure-compile-only requires at least one iteration"
         ));
+    }
+
+    let mut compile_times = measure_compile(function_path, iterations)?;
+    compile_times.sort();
+
+    // Nearest-rank percentiles
+    let percentile = |percent: usize| {
+        let rank = (percent * compile_times.len()).div_ceil(100).max(1);
+        compile_times[rank - 1].as_secs_f64() * 1000.0
     };
 
+    println!("Compile time over {iterations} iteration(s):");
+    println!("min: {:.2}ms", percentile(0));
+    println!("p50: {:.2}ms", percentile(50));
+    println!("p90: {:.2}ms", percentile(90));
+    println!("p99: {:.2}ms", percentile(99));
+    println!("max: {:.2}ms", percentile(100));
+
+    Ok(())
+}
+
+fn print_wat(function_path: &PathBuf) -> Result<()> {
+    let bytes = std::fs::read(function_path)
+        .map_err(|e| anyhow!("Couldn't load the Function {:?}: {}", function_path, e))?;
+
+    // Functions passed as .wat are already in the text format
+    if !bytes.starts_with(b"\0asm") {
+        print!("{}", String::from_utf8_lossy(&bytes));
+        return Ok(());
+    }
+
+    let wat = wasmprinter::print_bytes(&bytes).map_err(|e| {
+        anyhow!(
+            "Couldn't disassemble the Function {:?}: {}",
+            function_path,
+            e
+        )
+    })?;
+    println!("{wat}");
+
+    Ok(())
+}
+
+fn print_version_full() {
+    println!("function-runner {}", env!("CARGO_PKG_VERSION"));
+    println!("wasmtime {WASMTIME_VERSION}");
+    println!("providers:");
+    for provider in standard_providers() {
+        println!("  {provider}");
+    }
+}
+
+fn main() -> Result<()> {
+    let opts: Opts = Opts::parse_from(args_with_config(std::env::args_os().collect())?);
+
+    if opts.version_full {
+        print_version_full();
+        return Ok(());
+    }
+
+    if opts.show_wat {
+        return print_wat(&opts.function);
+    }
+
+    if let Some(iterations) = opts.measure_compile_only {
+        return print_compile_times(&opts.function, iterations);
+    }
+
+    if opts.show_selected_limits {
+        return print_selected_limits(&opts);
+    }
+
+    if let Some(expected_exports) = &opts.expected_exports {
+        check_expected_exports(&opts.function, expected_exports)?;
+    }
+
+    let input_path = opts.payload.as_ref().or(opts.input.as_ref());
+
+    let mut replay_codec = None;
+    let mut input: Box<dyn Read + Sync + Send + 'static> =
+        if let Some(input_base64) = &opts.input_base64 {
+            let bytes = BASE64_STANDARD
+                .decode(input_base64.trim())
+                .map_err(|e| anyhow!("Invalid base64 input: {}", e))?;
+            Box::new(Cursor::new(bytes))
+        } else if let Some(replay) = &opts.replay {
+            let (input, input_codec) = read_replay_input(replay)?;
+            replay_codec = Some(Codec::from_input_codec(input_codec));
+            Box::new(Cursor::new(input))
+        } else if let Some(0) = opts.input_fd {
+            Box::new(BufReader::new(stdin()))
+        } else if let Some(input_fd) = opts.input_fd {
+            Box::new(BufReader::new(file_from_fd(input_fd)?))
+        } else if let Some(input) = input_path {
+            Box::new(BufReader::new(File::open(input).map_err(|e| {
+                anyhow!("Couldn't load input {:?}: {}", input, e)
+            })?))
+        } else if !std::io::stdin().is_terminal() {
+            Box::new(BufReader::new(stdin()))
+        } else {
+            return Err(anyhow!(
+                "You must provide input via the --input flag or piped via stdin."
+            ));
+        };
+
     let mut buffer = Vec::new();
     input.read_to_end(&mut buffer)?;
 
-    let schema_string = opts.read_schema_to_string().transpose()?;
+    let input_compression = opts
+        .input_compression
+        .unwrap_or_else(|| Compression::from_path(input_path));
+    if input_compression == Compression::Zstd {
+        buffer = zstd::decode_all(buffer.as_slice())
+            .map_err(|e| anyhow!("Couldn't decompress input: {}", e))?;
+    }
+
+    if opts.payload.is_some() {
+        validate_payload(&buffer)?;
+    }
+
+    let baseline = opts
+        .compare_baseline
+        .as_ref()
+        .map(read_baseline)
+        .transpose()?;
+
+    let (schema_string, query_string) = match opts.graphql_document.as_ref() {
+        Some(graphql_document) => {
+            let (schema_string, query_string) = BluejaySchemaAnalyzer::split_document(
+                &read_file_to_string(graphql_document)?,
+                graphql_document.to_str(),
+            )?;
+            (Some(schema_string), Some(query_string))
+        }
+        None => {
+            let schema_string = opts.read_schema_to_string().transpose()?;
+            let query_string = match (opts.assume_full_query, schema_string.as_ref()) {
+                (true, Some(schema_string)) => Some(BluejaySchemaAnalyzer::full_query(
+                    schema_string,
+                    opts.schema_path.as_ref().and_then(|p| p.to_str()),
+                )?),
+                _ => opts.read_query_to_string().transpose()?,
+            };
+            (schema_string, query_string)
+        }
+    };
 
-    let query_string = opts.read_query_to_string().transpose()?;
+    let codec = match replay_codec.unwrap_or(opts.codec) {
+        Codec::Auto => Codec::detect(&buffer),
+        codec => codec,
+    };
 
-    let (json_value, buffer) = match opts.codec {
+    let (json_value, buffer) = match codec {
         Codec::Json => {
-            let json = serde_json::from_slice::<serde_json::Value>(&buffer)
+            let mut json = serde_json::from_slice::<serde_json::Value>(&buffer)
                 .map_err(|e| anyhow!("Invalid input JSON: {}", e))?;
+            if let Some(input_jsonpath) = &opts.input_jsonpath {
+                json = select_input(&json, input_jsonpath)?;
+            }
+            apply_input_transforms(&opts.input_transform, &mut json)?;
             let minified_buffer =
                 serde_json::to_vec(&json).map_err(|e| anyhow!("Couldn't serialize JSON: {}", e))?;
             (Some(json), minified_buffer)
         }
+        Codec::Raw if opts.input_jsonpath.is_some() => {
+            return Err(anyhow!("--input-jsonpath requires a JSON input codec"));
+        }
+        Codec::Raw if !opts.input_transform.is_empty() => {
+            return Err(anyhow!("--input-transform requires a JSON input codec"));
+        }
         Codec::Raw => (None, buffer),
         Codec::JsonToMessagepack => {
-            let json: serde_json::Value = serde_json::from_slice(&buffer)
+            let mut json: serde_json::Value = serde_json::from_slice(&buffer)
                 .map_err(|e| anyhow!("Invalid input JSON: {}", e))?;
+            if let Some(input_jsonpath) = &opts.input_jsonpath {
+                json = select_input(&json, input_jsonpath)?;
+            }
+            apply_input_transforms(&opts.input_transform, &mut json)?;
             let bytes = rmp_serde::to_vec(&json)
                 .map_err(|e| anyhow!("Couldn't convert JSON to MessagePack: {}", e))?;
             (Some(json), bytes)
         }
+        Codec::Messagepack => {
+            let mut json = InputCodec::Messagepack.decode(&buffer)?;
+            if opts.input_jsonpath.is_none() && opts.input_transform.is_empty() {
+                (Some(json), buffer)
+            } else {
+                if let Some(input_jsonpath) = &opts.input_jsonpath {
+                    json = select_input(&json, input_jsonpath)?;
+                }
+                apply_input_transforms(&opts.input_transform, &mut json)?;
+                let bytes = rmp_serde::to_vec(&json)
+                    .map_err(|e| anyhow!("Couldn't convert JSON to MessagePack: {}", e))?;
+                (Some(json), bytes)
+            }
+        }
+        Codec::Auto => unreachable!("the codec was detected"),
     };
 
+    if let Some(encoding) = opts.print_input_encoded {
+        match encoding {
+            Encoding::Hex => {
+                for byte in &buffer {
+                    print!("{byte:02x}");
+                }
+                println!();
+            }
+            Encoding::Base64 => println!("{}", BASE64_STANDARD.encode(&buffer)),
+        }
+        return Ok(());
+    }
+
     let scale_factor = if let (Some(schema_string), Some(query_string), Some(json_value)) =
-        (schema_string, query_string, json_value)
+        (schema_string, query_string, json_value.as_ref())
     {
-        BluejaySchemaAnalyzer::analyze_schema_definition(
+        let graphql_document = opts.graphql_document.as_ref().and_then(|p| p.to_str());
+        let raw_scale_factor = BluejaySchemaAnalyzer::raw_scale_factor(
             &schema_string,
-            opts.schema_path.as_ref().and_then(|p| p.to_str()),
+            graphql_document.or(opts.schema_path.as_ref().and_then(|p| p.to_str())),
             &query_string,
-            opts.query_path.as_ref().and_then(|p| p.to_str()),
-            &json_value,
-        )?
+            graphql_document.or(opts.query_path.as_ref().and_then(|p| p.to_str())),
+            json_value,
+        )?;
+        let scale_factor = ScaleLimits::clamp_scale_factor(raw_scale_factor);
+        if raw_scale_factor > scale_factor {
+            eprintln!(
+                "Scale factor clamped from {:.1} to {:.1}, the input is over the maximum scaling",
+                raw_scale_factor, scale_factor
+            );
+        }
+        scale_factor
     } else {
         DEFAULT_SCALE_FACTOR // Use default scale factor when schema or query is missing
     };
 
     let profile_opts = opts.profile_opts();
+    let display_opts = opts.display_opts();
+    let machine_readable = opts.machine_readable();
 
-    let function_run_result = run(FunctionRunParams {
-        function_path: opts.function,
-        input: buffer,
-        export: opts.export.as_ref(),
-        profile_opts: profile_opts.as_ref(),
+    if opts.export_all {
+        return run_all_exports(
+            &opts,
+            buffer,
+            codec.input_codec(),
+            json_value,
+            scale_factor,
+            &display_opts,
+        );
+    }
+
+    if opts.compare_codec {
+        let json_value =
+            json_value.ok_or_else(|| anyhow!("--compare-codec requires a JSON input codec"))?;
+        return compare_codecs(&opts, &json_value, scale_factor);
+    }
+
+    let verification_run_result = if opts.verify_determinism {
+        Some(run(opts.function_run_params(
+            buffer.clone(),
+            codec.input_codec(),
+            json_value.clone(),
+            scale_factor,
+            None,
+        ))?)
+    } else {
+        None
+    };
+
+    let mut function_run_result = run(opts.function_run_params(
+        buffer,
+        codec.input_codec(),
+        json_value,
         scale_factor,
-    })?;
+        profile_opts.as_ref(),
+    ))?;
+
+    if let Some(verification_run_result) = verification_run_result {
+        function_run_result.deterministic = Some(
+            function_run_result.instructions == verification_run_result.instructions
+                && function_run_result.compute_output_hash()
+                    == verification_run_result.compute_output_hash(),
+        );
+    }
+
+    if let Some(metric_prefix) = opts.metric_prefix.as_deref() {
+        function_run_result.extract_metrics(metric_prefix);
+    }
+
+    if opts.output_hash {
+        function_run_result.output_hash = Some(function_run_result.compute_output_hash());
+    }
+
+    // Assertions check the actual output, before any redaction
+    let output = function_run_result
+        .output_json()
+        .unwrap_or(&serde_json::Value::Null);
+    let failed_assertions: Vec<anyhow::Error> = opts
+        .assertions
+        .iter()
+        .filter_map(|assertion| assertion.check(output).err())
+        .collect();
 
-    if opts.json {
+    for pointer in &opts.redact {
+        function_run_result.redact(pointer)?;
+    }
+
+    if opts.output_only {
+        match &function_run_result.output {
+            FunctionOutput::JsonOutput(output) => println!(
+                "{}",
+                serde_json::to_string_pretty(output)
+                    .map_err(|e| anyhow!("Couldn't serialize JSON: {}", e))?
+            ),
+            FunctionOutput::InvalidJsonOutput(invalid_output) => {
+                println!("{}", invalid_output.stdout)
+            }
+        }
+    } else if opts.json_compact {
+        println!("{}", function_run_result.to_json_compact());
+    } else if opts.json {
         println!("{}", function_run_result.to_json());
     } else {
-        println!("{function_run_result}");
+        println!(
+            "{}",
+            function_run_result.render(&display_opts, opts.colorize())
+        );
+    }
+
+    if let Some(baseline) = baseline.as_ref() {
+        let comparison = function_run_result.render_comparison(
+            baseline,
+            opts.regression_threshold,
+            opts.colorize(),
+        );
+        if machine_readable {
+            eprintln!("{comparison}");
+        } else {
+            println!("Compared to baseline:\n{comparison}\n");
+        }
+    }
+
+    if opts.trace_memory {
+        let memory_growths: Vec<String> = function_run_result
+            .memory_growths
+            .iter()
+            .map(|(from, to)| format!("{from} -> {to} pages"))
+            .collect();
+        let memory_growths = memory_growths.join("\n");
+        if machine_readable {
+            eprintln!("{memory_growths}");
+        } else {
+            println!("Memory growths:\n{memory_growths}\n");
+        }
+    }
+
+    let instructions_per_input_byte = match (
+        opts.max_instructions_per_input_byte,
+        function_run_result.input_size(),
+    ) {
+        (Some(_), 0) => {
+            eprintln!("Instructions per input byte can't be computed, the input is empty");
+            None
+        }
+        (Some(max_instructions_per_input_byte), input_size) => {
+            let instructions_per_input_byte =
+                function_run_result.instructions as f64 / input_size as f64;
+            let report = format!(
+                "Instructions per input byte: {instructions_per_input_byte:.2} (limit {max_instructions_per_input_byte})"
+            );
+            if machine_readable {
+                eprintln!("{report}");
+            } else {
+                println!("{report}\n");
+            }
+            Some(instructions_per_input_byte)
+        }
+        (None, _) => None,
+    };
+
+    if let Some(count) = opts.profile_top {
+        let hottest_stacks: Vec<String> = function_run_result
+            .hottest_stacks(count)
+            .into_iter()
+            .map(|(stack, weight)| format!("{weight:>12} {stack}"))
+            .collect();
+        let hottest_stacks = hottest_stacks.join("\n");
+        if machine_readable {
+            eprintln!("{hottest_stacks}");
+        } else {
+            println!("Hottest stacks:\n{hottest_stacks}\n");
+        }
     }
 
     if let Some(profile) = function_run_result.profile.as_ref() {
-        std::fs::write(profile_opts.unwrap().out, profile)?;
+        let profile_out = profile_opts.unwrap().out;
+        let profile_compression = opts
+            .profile_compression
+            .unwrap_or_else(|| Compression::from_path(Some(&profile_out)));
+        match profile_compression {
+            Compression::None => std::fs::write(profile_out, profile)?,
+            Compression::Zstd => std::fs::write(
+                profile_out,
+                zstd::encode_all(profile.as_bytes(), 0)
+                    .map_err(|e| anyhow!("Couldn't compress profile: {}", e))?,
+            )?,
+        }
+    }
+
+    if let (Some(memory_dump), Some(dump_on_trap)) = (
+        function_run_result.memory_dump.as_ref(),
+        opts.dump_on_trap.as_ref(),
+    ) {
+        std::fs::write(dump_on_trap, memory_dump)?;
+    }
+
+    if let Some(benchmark_json) = opts.benchmark_json.as_ref() {
+        append_benchmark_json(benchmark_json, opts.label, &function_run_result)?;
     }
 
-    if function_run_result.success {
+    if !function_run_result.success {
+        anyhow::bail!("The Function execution failed. Review the logs for more information.")
+    }
+
+    if function_run_result.deterministic == Some(false) {
+        anyhow::bail!(
+            "The Function isn't deterministic, its output or instruction count changed between two runs."
+        )
+    }
+
+    if let (Some(instructions_per_input_byte), Some(max_instructions_per_input_byte)) = (
+        instructions_per_input_byte,
+        opts.max_instructions_per_input_byte,
+    ) {
+        if instructions_per_input_byte > max_instructions_per_input_byte {
+            anyhow::bail!(
+                "The Function executed {:.2} instructions per input byte, more than the limit of {}",
+                instructions_per_input_byte,
+                max_instructions_per_input_byte
+            )
+        }
+    }
+
+    if failed_assertions.is_empty() {
         Ok(())
     } else {
-        anyhow::bail!("The Function execution failed. Review the logs for more information.")
+        for failed_assertion in &failed_assertions {
+            eprintln!("{failed_assertion}");
+        }
+        anyhow::bail!("{} output assertion(s) failed", failed_assertions.len())
     }
 }
@@ -0,0 +1,206 @@
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use std::{fmt, str::FromStr};
+
+/// A check made against the output JSON after the Function runs.
+///
+/// Assertions are written as `<pointer>`, to check that a value exists, or `<pointer><op><json>`, to compare it
+/// with a JSON literal, where `<op>` is `==` or `!=`:
+/// * `/cart/total` checks that `/cart/total` exists
+/// * `/cart/total==42` checks that `/cart/total` equals `42`, as does `42.0`
+/// * `/cart/note!="a!=b"` checks that `/cart/note` exists and isn't the string `"a!=b"`
+///
+/// The pointer ends at the first `==` or `!=`, and everything after the operator must be valid JSON, so strings
+/// are quoted. Numbers are compared by value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OutputAssertion {
+    Exists(String),
+    Equals(String, Value),
+    NotEquals(String, Value),
+}
+
+impl FromStr for OutputAssertion {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let operator = [s.find("=="), s.find("!=")].into_iter().flatten().min();
+
+        let assertion = match operator {
+            Some(index) => {
+                let (pointer, expected) = (&s[..index], &s[index + 2..]);
+                let expected = serde_json::from_str(expected).map_err(|e| {
+                    anyhow!(
+                        "Invalid assertion `{}`, `{}` is not JSON (strings must be quoted): {}",
+                        s,
+                        expected,
+                        e
+                    )
+                })?;
+                if s[index..].starts_with("==") {
+                    OutputAssertion::Equals(pointer.to_string(), expected)
+                } else {
+                    OutputAssertion::NotEquals(pointer.to_string(), expected)
+                }
+            }
+            None => OutputAssertion::Exists(s.to_string()),
+        };
+
+        let pointer = assertion.pointer();
+        if !pointer.is_empty() && !pointer.starts_with('/') {
+            return Err(anyhow!(
+                "Invalid assertion `{}`, `{}` is not a JSON Pointer",
+                s,
+                pointer
+            ));
+        }
+
+        Ok(assertion)
+    }
+}
+
+impl fmt::Display for OutputAssertion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputAssertion::Exists(pointer) => write!(f, "{pointer}"),
+            OutputAssertion::Equals(pointer, expected) => write!(f, "{pointer}=={expected}"),
+            OutputAssertion::NotEquals(pointer, expected) => write!(f, "{pointer}!={expected}"),
+        }
+    }
+}
+
+impl OutputAssertion {
+    fn pointer(&self) -> &str {
+        match self {
+            OutputAssertion::Exists(pointer)
+            | OutputAssertion::Equals(pointer, _)
+            | OutputAssertion::NotEquals(pointer, _) => pointer,
+        }
+    }
+
+    /// Check the assertion against the output, describing the mismatch if it fails.
+    pub fn check(&self, output: &Value) -> Result<()> {
+        let actual = output.pointer(self.pointer()).ok_or_else(|| {
+            anyhow!(
+                "Assertion `{}` failed: no value at `{}`",
+                self,
+                self.pointer()
+            )
+        })?;
+
+        match self {
+            OutputAssertion::Exists(_) => Ok(()),
+            OutputAssertion::Equals(_, expected) if json_eq(actual, expected) => Ok(()),
+            OutputAssertion::NotEquals(_, expected) if !json_eq(actual, expected) => Ok(()),
+            _ => Err(anyhow!(
+                "Assertion `{}` failed: `{}` is {}",
+                self,
+                self.pointer(),
+                actual
+            )),
+        }
+    }
+}
+
+/// Equality of JSON values where numbers are compared by value, e.g. `42 == 42.0`.
+fn json_eq(value: &Value, other: &Value) -> bool {
+    match (value, other) {
+        (Value::Number(number), Value::Number(other_number)) => {
+            match (number.as_i64(), other_number.as_i64()) {
+                (Some(integer), Some(other_integer)) => integer == other_integer,
+                _ => match (number.as_u64(), other_number.as_u64()) {
+                    (Some(integer), Some(other_integer)) => integer == other_integer,
+                    _ => number.as_f64() == other_number.as_f64(),
+                },
+            }
+        }
+        (Value::Array(array), Value::Array(other_array)) => {
+            array.len() == other_array.len()
+                && array
+                    .iter()
+                    .zip(other_array)
+                    .all(|(value, other)| json_eq(value, other))
+        }
+        (Value::Object(object), Value::Object(other_object)) => {
+            object.len() == other_object.len()
+                && object.iter().all(|(key, value)| {
+                    other_object
+                        .get(key)
+                        .is_some_and(|other| json_eq(value, other))
+                })
+        }
+        _ => value == other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            "/cart/total==42".parse::<OutputAssertion>().unwrap(),
+            OutputAssertion::Equals("/cart/total".to_string(), json!(42))
+        );
+        assert_eq!(
+            "/status!=\"ok\"".parse::<OutputAssertion>().unwrap(),
+            OutputAssertion::NotEquals("/status".to_string(), json!("ok"))
+        );
+        assert_eq!(
+            "/note==\"a!=b\"".parse::<OutputAssertion>().unwrap(),
+            OutputAssertion::Equals("/note".to_string(), json!("a!=b"))
+        );
+        assert_eq!(
+            "/note!=\"a==b\"".parse::<OutputAssertion>().unwrap(),
+            OutputAssertion::NotEquals("/note".to_string(), json!("a==b"))
+        );
+        assert_eq!(
+            "/cart".parse::<OutputAssertion>().unwrap(),
+            OutputAssertion::Exists("/cart".to_string())
+        );
+        assert!("cart==1".parse::<OutputAssertion>().is_err());
+        assert!("/status==ok".parse::<OutputAssertion>().is_err());
+    }
+
+    #[test]
+    fn test_check() {
+        let output = json!({"cart": {"total": 42, "note": null}});
+
+        for assertion in [
+            "/cart",
+            "/cart/total==42",
+            "/cart/total==42.0",
+            "/cart/total!=41",
+            "/cart/total!=42.5",
+            "/cart/note==null",
+            "/cart=={\"total\": 42.0, \"note\": null}",
+        ] {
+            assertion
+                .parse::<OutputAssertion>()
+                .unwrap()
+                .check(&output)
+                .unwrap();
+        }
+
+        let error = "/cart/total==41"
+            .parse::<OutputAssertion>()
+            .unwrap()
+            .check(&output)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Assertion `/cart/total==41` failed: `/cart/total` is 42"
+        );
+
+        let error = "/cart/lines"
+            .parse::<OutputAssertion>()
+            .unwrap()
+            .check(&output)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Assertion `/cart/lines` failed: no value at `/cart/lines`"
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use anyhow::{anyhow, Result};
+use std::{any::Any, fmt, path::PathBuf, str::FromStr};
+use wasi_common::{
+    dir::{OpenResult, ReaddirCursor, ReaddirEntity, WasiDir},
+    file::{FdFlags, Filestat, OFlags},
+    sync::{ambient_authority, dir::Dir as WasiSyncDir, Dir},
+    Error, ErrorExt,
+};
+
+/// A host directory made readable to the Function under a guest path, written as `<guest-path>=<host-path>`.
+///
+/// Preopened directories are read-only: the Function can't create, change or remove anything in them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Preopen {
+    pub guest_path: String,
+    pub host_path: PathBuf,
+}
+
+impl FromStr for Preopen {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.split_once('=') {
+            Some((guest_path, host_path)) if !guest_path.is_empty() && !host_path.is_empty() => {
+                Ok(Preopen {
+                    guest_path: guest_path.to_string(),
+                    host_path: PathBuf::from(host_path),
+                })
+            }
+            _ => Err(anyhow!(
+                "Invalid preopen `{}`, expected `<guest-path>=<host-path>`",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Preopen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.guest_path, self.host_path.display())
+    }
+}
+
+impl Preopen {
+    /// Open the host directory for the Function.
+    pub fn open(&self) -> Result<Box<dyn WasiDir>> {
+        let dir = Dir::open_ambient_dir(&self.host_path, ambient_authority()).map_err(|e| {
+            anyhow!(
+                "Couldn't open preopened directory {:?}: {}",
+                self.host_path,
+                e
+            )
+        })?;
+
+        Ok(Box::new(ReadOnlyDir(Box::new(WasiSyncDir::from_cap_std(
+            dir,
+        )))))
+    }
+}
+
+/// Forwards the reads of a directory and refuses its writes.
+struct ReadOnlyDir(Box<dyn WasiDir>);
+
+#[async_trait::async_trait]
+impl WasiDir for ReadOnlyDir {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    async fn open_file(
+        &self,
+        symlink_follow: bool,
+        path: &str,
+        oflags: OFlags,
+        read: bool,
+        write: bool,
+        fdflags: FdFlags,
+    ) -> Result<OpenResult, Error> {
+        if write || (!oflags.is_empty() && oflags != OFlags::DIRECTORY) {
+            return Err(Error::perm());
+        }
+
+        match self
+            .0
+            .open_file(symlink_follow, path, oflags, read, write, fdflags)
+            .await?
+        {
+            OpenResult::Dir(dir) => Ok(OpenResult::Dir(Box::new(ReadOnlyDir(dir)))),
+            file => Ok(file),
+        }
+    }
+
+    async fn readdir(
+        &self,
+        cursor: ReaddirCursor,
+    ) -> Result<Box<dyn Iterator<Item = Result<ReaddirEntity, Error>> + Send>, Error> {
+        self.0.readdir(cursor).await
+    }
+
+    async fn read_link(&self, path: &str) -> Result<PathBuf, Error> {
+        self.0.read_link(path).await
+    }
+
+    async fn get_filestat(&self) -> Result<Filestat, Error> {
+        self.0.get_filestat().await
+    }
+
+    async fn get_path_filestat(
+        &self,
+        path: &str,
+        follow_symlinks: bool,
+    ) -> Result<Filestat, Error> {
+        self.0.get_path_filestat(path, follow_symlinks).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            "/config=tests/fixtures".parse::<Preopen>().unwrap(),
+            Preopen {
+                guest_path: "/config".to_string(),
+                host_path: PathBuf::from("tests/fixtures"),
+            }
+        );
+        assert!("/config".parse::<Preopen>().is_err());
+        assert!("=tests/fixtures".parse::<Preopen>().is_err());
+    }
+}
@@ -0,0 +1,82 @@
+use std::{error::Error, fmt};
+
+use crate::function_run_result::FunctionRunResult;
+
+/// Why running a Function failed, for embedders that need to handle each failure mode distinctly.
+///
+/// Failures that happen once the Function has started carry its run result, e.g. to inspect the logs.
+#[derive(Debug)]
+pub enum RunError {
+    /// The module couldn't be read or compiled.
+    ModuleLoad(String),
+    /// The module imports WASI although it's forbidden.
+    ForbiddenImport(String),
+    /// The requested export doesn't exist or isn't a `() -> ()` function.
+    InvalidExport(String),
+    /// The input couldn't be decoded with its codec.
+    InvalidInput(String),
+    /// The runner couldn't set up the Function's environment, e.g. open a preopened directory.
+    Setup(anyhow::Error),
+    /// The module couldn't be linked or instantiated, e.g. because of an unknown import.
+    Instantiation(anyhow::Error),
+    /// The Function trapped or exited with a non-zero exit code.
+    Trap {
+        message: String,
+        result: Box<FunctionRunResult>,
+    },
+    /// The Function exceeded its instruction limit.
+    OutOfFuel { result: Box<FunctionRunResult> },
+    /// The Function ran longer than its timeout.
+    Timeout { result: Box<FunctionRunResult> },
+    /// The Function completed but its output isn't valid JSON.
+    InvalidOutput {
+        message: String,
+        result: Box<FunctionRunResult>,
+    },
+}
+
+impl RunError {
+    /// The run result, if the Function started running.
+    pub fn result(&self) -> Option<&FunctionRunResult> {
+        match self {
+            RunError::ModuleLoad(_)
+            | RunError::ForbiddenImport(_)
+            | RunError::InvalidExport(_)
+            | RunError::InvalidInput(_)
+            | RunError::Setup(_)
+            | RunError::Instantiation(_) => None,
+            RunError::Trap { result, .. }
+            | RunError::OutOfFuel { result }
+            | RunError::Timeout { result }
+            | RunError::InvalidOutput { result, .. } => Some(result),
+        }
+    }
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunError::ModuleLoad(message)
+            | RunError::ForbiddenImport(message)
+            | RunError::InvalidExport(message)
+            | RunError::InvalidInput(message) => write!(f, "{message}"),
+            RunError::Setup(_) => write!(f, "Couldn't set up the Function's environment"),
+            RunError::Instantiation(_) => write!(f, "The Function couldn't be instantiated"),
+            RunError::Trap { message, .. } => write!(f, "The Function trapped: {message}"),
+            RunError::OutOfFuel { .. } => write!(f, "The Function ran out of fuel"),
+            RunError::Timeout { .. } => write!(f, "The Function timed out"),
+            RunError::InvalidOutput { message, .. } => {
+                write!(f, "The Function produced invalid output: {message}")
+            }
+        }
+    }
+}
+
+impl Error for RunError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RunError::Setup(error) | RunError::Instantiation(error) => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
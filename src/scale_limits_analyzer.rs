@@ -140,30 +140,131 @@ impl<'a>
         normalized_rates
             .into_values()
             .fold(Self::MIN_SCALE_FACTOR, f64::max)
-            .clamp(Self::MIN_SCALE_FACTOR, Self::MAX_SCALE_FACTOR)
     }
 }
 
 impl ScaleLimits<'_> {
-    const MIN_SCALE_FACTOR: f64 = 1.0;
-    const MAX_SCALE_FACTOR: f64 = 10.0;
+    pub const MIN_SCALE_FACTOR: f64 = 1.0;
+    pub const MAX_SCALE_FACTOR: f64 = 10.0;
 
-    fn rate_for_field_definition(
+    /// Clamps a raw scale factor to the bounds applied in production.
+    pub fn clamp_scale_factor(raw_scale_factor: f64) -> f64 {
+        raw_scale_factor.clamp(Self::MIN_SCALE_FACTOR, Self::MAX_SCALE_FACTOR)
+    }
+
+    /// Returns the highest `rate` across all `@scaleLimits` directives on the field, if any.
+    pub(crate) fn rate_for_field_definition(
         field_definition: &FieldDefinition<DefaultContext>,
     ) -> Option<f64> {
         field_definition
             .directives()
             .iter()
             .flat_map(|directives| directives.iter())
-            .find(|directive| directive.name() == "scaleLimits")
-            .and_then(|directive| directive.arguments())
-            .and_then(|arguments| arguments.iter().find(|argument| argument.name() == "rate"))
-            .and_then(|argument| {
+            .filter(|directive| directive.name() == "scaleLimits")
+            .filter_map(|directive| directive.arguments())
+            .filter_map(|arguments| arguments.iter().find(|argument| argument.name() == "rate"))
+            .filter_map(|argument| {
                 if let ValueReference::Float(rate) = argument.value().as_ref() {
                     Some(rate)
                 } else {
                     None
                 }
             })
+            .reduce(f64::max)
+    }
+}
+
+pub type SelectedScaleLimitsAnalyzer<'a> = bluejay_validator::executable::operation::Orchestrator<
+    'a,
+    ExecutableDocument<'a>,
+    SchemaDefinition<'a>,
+    serde_json::Map<String, serde_json::Value>,
+    SelectedScaleLimits<'a>,
+>;
+
+/// A field selected by the query whose definition has a `@scaleLimits` directive.
+#[derive(Debug, PartialEq)]
+pub struct SelectedScaleLimit {
+    /// Dot-separated response keys from the query root, e.g. `cart.lines`.
+    pub path: String,
+    pub rate: f64,
+}
+
+/// Collects the `@scaleLimits` fields selected by a query, independently of any input.
+pub struct SelectedScaleLimits<'a> {
+    path_stack: Vec<&'a str>,
+    selected: Vec<SelectedScaleLimit>,
+}
+
+impl<'a>
+    bluejay_validator::executable::operation::Visitor<
+        'a,
+        ExecutableDocument<'a>,
+        SchemaDefinition<'a>,
+        serde_json::Map<String, serde_json::Value>,
+    > for SelectedScaleLimits<'a>
+{
+    type ExtraInfo = ();
+
+    fn new(
+        _operation_definition: &'a <ExecutableDocument as bluejay_core::executable::ExecutableDocument>::OperationDefinition,
+        _schema_definition: &'a SchemaDefinition<'a>,
+        _variable_values: &'a serde_json::Map<String, serde_json::Value>,
+        _cache: &'a bluejay_validator::executable::Cache<'a, ExecutableDocument, SchemaDefinition>,
+        _extra_info: (),
+    ) -> Self {
+        Self {
+            path_stack: Vec::new(),
+            selected: Vec::new(),
+        }
+    }
+
+    fn visit_field(
+        &mut self,
+        field: &'a <ExecutableDocument<'a> as bluejay_core::executable::ExecutableDocument>::Field,
+        field_definition: &'_ <SchemaDefinition as CoreSchemaDefinition>::FieldDefinition,
+        _scoped_type: bluejay_core::definition::TypeDefinitionReference<
+            '_,
+            <SchemaDefinition<'_> as CoreSchemaDefinition>::TypeDefinition,
+        >,
+        _included: bool,
+    ) {
+        self.path_stack.push(field.response_key());
+
+        if let Some(rate) = ScaleLimits::rate_for_field_definition(field_definition) {
+            let path = self.path_stack.join(".");
+            // Fragments spread more than once visit the same field several times
+            if !self.selected.iter().any(|selected| selected.path == path) {
+                self.selected.push(SelectedScaleLimit { path, rate });
+            }
+        }
+    }
+
+    fn leave_field(
+        &mut self,
+        _field: &'a <ExecutableDocument<'a> as bluejay_core::executable::ExecutableDocument>::Field,
+        _field_definition: &'a <SchemaDefinition<'a> as CoreSchemaDefinition>::FieldDefinition,
+        _scoped_type: bluejay_core::definition::TypeDefinitionReference<
+            'a,
+            <SchemaDefinition<'a> as CoreSchemaDefinition>::TypeDefinition,
+        >,
+        _included: bool,
+    ) {
+        self.path_stack.pop().unwrap();
+    }
+}
+
+impl<'a>
+    bluejay_validator::executable::operation::Analyzer<
+        'a,
+        ExecutableDocument<'a>,
+        SchemaDefinition<'a>,
+        serde_json::Map<String, serde_json::Value>,
+    > for SelectedScaleLimits<'a>
+{
+    type Output = Vec<SelectedScaleLimit>;
+
+    fn into_output(self) -> Self::Output {
+        self.selected
     }
 }
@@ -0,0 +1,17 @@
+(module
+  (import "wasi_snapshot_preview1" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  ;; iovecs at 0 and 8, written byte counts at 100
+  (data (i32.const 0) "\20\00\00\00\07\00\00\00")
+  (data (i32.const 8) "\30\00\00\00\07\00\00\00")
+  (data (i32.const 32) "{\"a\":1}")
+  (data (i32.const 48) "{\"b\":2}")
+  (func $a
+    (drop (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 100))))
+  (func $b
+    (drop (call $fd_write (i32.const 1) (i32.const 8) (i32.const 1) (i32.const 100))))
+  (func $with_param (param i32))
+  (export "a" (func $a))
+  (export "b" (func $b))
+  (export "with_param" (func $with_param))
+)
@@ -0,0 +1,5 @@
+(module
+  (memory (export "memory") 1)
+  (func $_start (loop $forever (br $forever)))
+  (export "_start" (func $_start))
+)
@@ -0,0 +1,7 @@
+(module
+  (memory (export "memory") 1)
+  (func $_start
+    ;; Grow by 2 pages of 64KiB, to 3 pages
+    (drop (memory.grow (i32.const 2))))
+  (export "_start" (func $_start))
+)
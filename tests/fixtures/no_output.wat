@@ -0,0 +1,5 @@
+(module
+  (memory (export "memory") 1)
+  (func $_start) ;; exits successfully without writing anything to stdout
+  (export "_start" (func $_start))
+)
@@ -0,0 +1,37 @@
+(module
+  (import "wasi_snapshot_preview1" "path_open"
+    (func $path_open (param i32 i32 i32 i32 i32 i64 i64 i32 i32) (result i32)))
+  (import "wasi_snapshot_preview1" "fd_read" (func $fd_read (param i32 i32 i32 i32) (result i32)))
+  (import "wasi_snapshot_preview1" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  ;; opened fd at 0, byte count at 4, iovec at 8 pointing to the buffer at 1024
+  (data (i32.const 8) "\00\04\00\00\00\04\00\00")
+  (data (i32.const 32) "config.json")
+  (data (i32.const 48) "out.json")
+  (data (i32.const 64) "{\"denied\":true}")
+  (data (i32.const 80) "{\"denied\":false}")
+
+  ;; Prints the contents of config.json in the first preopened directory
+  (func $read_config
+    (if (call $path_open (i32.const 3) (i32.const 0) (i32.const 32) (i32.const 11) (i32.const 0)
+          (i64.const 2) (i64.const 0) (i32.const 0) (i32.const 0))
+      (then unreachable))
+    (drop (call $fd_read (i32.load (i32.const 0)) (i32.const 8) (i32.const 1) (i32.const 4)))
+    (i32.store (i32.const 12) (i32.load (i32.const 4)))
+    (drop (call $fd_write (i32.const 1) (i32.const 8) (i32.const 1) (i32.const 4))))
+
+  ;; Prints whether creating out.json for writing in the first preopened directory was denied
+  (func $write_file
+    (if (call $path_open (i32.const 3) (i32.const 0) (i32.const 48) (i32.const 8) (i32.const 1)
+          (i64.const 64) (i64.const 0) (i32.const 0) (i32.const 0))
+      (then
+        (i32.store (i32.const 8) (i32.const 64))
+        (i32.store (i32.const 12) (i32.const 15)))
+      (else
+        (i32.store (i32.const 8) (i32.const 80))
+        (i32.store (i32.const 12) (i32.const 16))))
+    (drop (call $fd_write (i32.const 1) (i32.const 8) (i32.const 1) (i32.const 4))))
+
+  (export "_start" (func $read_config))
+  (export "write_file" (func $write_file))
+)
@@ -0,0 +1,8 @@
+(module
+  (memory (export "memory") 1)
+  (func $_start)
+  (func $run (param i32 i64) (result i32)
+    (local.get 0))
+  (export "_start" (func $_start))
+  (export "run" (func $run))
+)
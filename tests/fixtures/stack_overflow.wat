@@ -0,0 +1,8 @@
+(module
+  (memory (export "memory") 1)
+  (func $recurse (param i64 i64 i64 i64) (result i64)
+    (call $recurse (local.get 0) (local.get 1) (local.get 2) (local.get 3)))
+  (func $_start
+    (drop (call $recurse (i64.const 0) (i64.const 0) (i64.const 0) (i64.const 0))))
+  (export "_start" (func $_start))
+)
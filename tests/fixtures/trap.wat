@@ -0,0 +1,6 @@
+(module
+  (memory (export "memory") 1)
+  (data (i32.const 0) "trap")
+  (func $_start unreachable)
+  (export "_start" (func $_start))
+)
@@ -0,0 +1,9 @@
+(module
+  (import "env" "missing" (func $missing (param i32) (result i32)))
+  (memory (export "memory") 1)
+  (func $_start)
+  (func $call_missing
+    (drop (call $missing (i32.const 0))))
+  (export "_start" (func $_start))
+  (export "call_missing" (func $call_missing))
+)
@@ -3,7 +3,10 @@ mod tests {
 
     use assert_cmd::prelude::*;
     use assert_fs::prelude::*;
-    use function_runner::function_run_result::FunctionRunResult;
+    use function_runner::{
+        engine::WASMTIME_VERSION,
+        function_run_result::{FunctionRunResult, InputCodec},
+    };
     use predicates::prelude::*;
     use predicates::{prelude::predicate, str::contains};
     use serde_json::json;
@@ -62,6 +65,57 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn run_input_fd() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("function-runner")?;
+
+        let input_file = temp_input(json!({"count": 0}))?;
+        let file = File::open(input_file.path())?;
+
+        cmd.args(["--function", "tests/fixtures/build/noop.wasm"])
+            .args(["--input-fd", "0"])
+            .stdin(Stdio::from(file));
+        cmd.assert().success().stdout(contains("\"count\": 0"));
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn run_invalid_input_fd() -> Result<(), Box<dyn std::error::Error>> {
+        for (input_fd, error) in [
+            ("1", "Invalid --input-fd 1: only stdin (0) and descriptors"),
+            ("2", "Invalid --input-fd 2: only stdin (0) and descriptors"),
+            ("4242", "Invalid --input-fd 4242: Bad file descriptor"),
+            ("-1", "invalid value '-1' for '--input-fd <INPUT_FD>'"),
+        ] {
+            Command::cargo_bin("function-runner")?
+                .args(["--function", "tests/fixtures/build/noop.wasm"])
+                .arg(format!("--input-fd={input_fd}"))
+                .assert()
+                .failure()
+                .stderr(contains(error));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn show_wat() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("function-runner")?;
+
+        cmd.args(["--function", "tests/fixtures/build/trap.wasm"])
+            .arg("--show-wat");
+        cmd.assert()
+            .success()
+            .stdout(contains("(module"))
+            .stdout(contains("(export \"_start\" (func"))
+            .stdout(contains("unreachable"));
+
+        Ok(())
+    }
+
     #[test]
     fn run_no_opts() -> Result<(), Box<dyn std::error::Error>> {
         let mut cmd = Command::cargo_bin("function-runner")?;
@@ -79,37 +133,724 @@ mod tests {
         )
         .count(1);
 
-        assert!(predicate.eval(&actual));
+        assert!(predicate.eval(&actual));
+
+        Ok(())
+    }
+
+    #[test]
+    fn version_full() -> Result<(), Box<dyn std::error::Error>> {
+        assert_ne!(WASMTIME_VERSION, "unknown");
+
+        let mut cmd = Command::cargo_bin("function-runner")?;
+
+        cmd.arg("--version-full");
+        cmd.assert()
+            .success()
+            .stdout(contains(format!(
+                "function-runner {}",
+                env!("CARGO_PKG_VERSION")
+            )))
+            .stdout(contains(format!("wasmtime {WASMTIME_VERSION}\n")))
+            .stdout(contains("  javy_quickjs_provider_v3\n"))
+            .stdout(contains("  shopify_functions_javy_v1\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    #[ignore = "This test hangs on CI but runs locally, is_terminal is likely returning false in CI"]
+    fn run_function_no_input() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("function-runner")?;
+
+        cmd.args(["--function", "tests/fixtures/build/exit_code.wasm"]);
+        cmd.assert()
+            .failure()
+            .stderr("Error: You must provide input via the --input flag or piped via stdin.\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_json() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("function-runner")?;
+        let input_file = temp_input(json!({"count": 0}))?;
+
+        cmd.args(["--function", "tests/fixtures/build/noop.wasm"])
+            .arg("--json")
+            .arg("--input")
+            .arg(input_file.as_os_str());
+        cmd.assert().success();
+        let output = cmd.output().expect("Wasn't able to get output");
+        let _ = serde_json::from_slice::<FunctionRunResult>(&output.stdout)
+            .expect("This shouldn't fail");
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_json_compact() -> Result<(), Box<dyn std::error::Error>> {
+        let input_file = temp_input(json!({"count": 0}))?;
+
+        let output = Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/noop.wasm"])
+            .arg("--json-compact")
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .output()?;
+        let stdout = String::from_utf8(output.stdout)?;
+
+        assert_eq!(stdout.lines().count(), 1);
+        let _ = serde_json::from_str::<FunctionRunResult>(&stdout)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_output_only() -> Result<(), Box<dyn std::error::Error>> {
+        let input_file = temp_input(json!({"count": 0}))?;
+
+        let output = Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/noop.wasm"])
+            .arg("--output-only")
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .output()?;
+
+        assert!(output.status.success());
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&output.stdout)?,
+            json!({"count": 0})
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_export_all() -> Result<(), Box<dyn std::error::Error>> {
+        let input_file = temp_input(json!({}))?;
+
+        let output = Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/export_all.wasm"])
+            .args(["--export-all", "--output-only"])
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .output()?;
+
+        assert!(output.status.success());
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&output.stdout)?,
+            json!({"a": {"a": 1}, "b": {"b": 2}})
+        );
+        assert!(String::from_utf8(output.stderr)?.contains(
+            "Skipping `with_param`: Export `with_param` has signature (i32) -> (), expected () -> ()"
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn trace_fuel_per_export() -> Result<(), Box<dyn std::error::Error>> {
+        let input_file = temp_input(json!({}))?;
+
+        Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/export_all.wasm"])
+            .args(["--export-all", "--trace-fuel-per-export"])
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .assert()
+            .success()
+            .stdout(contains("Fuel per export:\na: 6 (50.00%)\nb: 6 (50.00%)\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_with_config() -> Result<(), Box<dyn std::error::Error>> {
+        let input_file = temp_input(json!({"count": 0}))?;
+        let config_file = assert_fs::NamedTempFile::new("function-runner.toml")?;
+        config_file.write_str(
+            "function = \"tests/fixtures/build/noop.wasm\"\nexport = \"missing\"\noutput_only = true\n",
+        )?;
+
+        let output = Command::cargo_bin("function-runner")?
+            .arg("--config")
+            .arg(config_file.as_os_str())
+            .args(["--export", "_start"])
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .output()?;
+
+        assert!(output.status.success());
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&output.stdout)?,
+            json!({"count": 0})
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn config_conflicting_with_cli() -> Result<(), Box<dyn std::error::Error>> {
+        let input_file = temp_input(json!({"count": 0}))?;
+        let config_file = assert_fs::NamedTempFile::new("function-runner.toml")?;
+        config_file
+            .write_str("function = \"tests/fixtures/build/noop.wasm\"\nexport_index = 99\n")?;
+
+        Command::cargo_bin("function-runner")?
+            .arg("--config")
+            .arg(config_file.as_os_str())
+            .args(["--export", "_start"])
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .assert()
+            .success();
+
+        Ok(())
+    }
+
+    #[test]
+    fn config_repeatable_flag_overridden_by_cli() -> Result<(), Box<dyn std::error::Error>> {
+        let input_file = temp_input(json!({"count": 0}))?;
+        let config_file = assert_fs::NamedTempFile::new("function-runner.toml")?;
+        config_file.write_str(
+            "function = \"tests/fixtures/build/noop.wasm\"\nassert = [\"/missing\", \"/count!=0\"]\n",
+        )?;
+
+        Command::cargo_bin("function-runner")?
+            .arg("--config")
+            .arg(config_file.as_os_str())
+            .args(["--assert", "/count==0"])
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .assert()
+            .success();
+
+        Command::cargo_bin("function-runner")?
+            .arg("--config")
+            .arg(config_file.as_os_str())
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .assert()
+            .failure()
+            .stderr(contains("Error: 2 output assertion(s) failed\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn config_unknown_flag() -> Result<(), Box<dyn std::error::Error>> {
+        let config_file = assert_fs::NamedTempFile::new("function-runner.toml")?;
+        config_file.write_str("functon = \"tests/fixtures/build/noop.wasm\"\n")?;
+
+        Command::cargo_bin("function-runner")?
+            .arg("--config")
+            .arg(config_file.as_os_str())
+            .assert()
+            .failure()
+            .stderr(contains("unknown flag `functon`"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn flags_ignored_by_mode() -> Result<(), Box<dyn std::error::Error>> {
+        for args in [
+            &["--export-all", "--assert", "/count"][..],
+            &["--export-all", "--profile"],
+            &["--compare-codec", "--json"],
+            &["--json", "--no-input-echo"],
+            &["--output-only", "--indent", "4"],
+        ] {
+            Command::cargo_bin("function-runner")?
+                .args(["--function", "tests/fixtures/build/noop.wasm"])
+                .args(args)
+                .assert()
+                .failure()
+                .stderr(contains("cannot be used with"));
+        }
+
+        for args in [
+            &["--codec-tolerance", "5"][..],
+            &["--regression-threshold", "5"],
+            &["--label", "main"],
+            &["--profile-compression", "zstd"],
+        ] {
+            Command::cargo_bin("function-runner")?
+                .args(["--function", "tests/fixtures/build/noop.wasm"])
+                .args(args)
+                .assert()
+                .failure()
+                .stderr(contains(
+                    "the following required arguments were not provided",
+                ));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_with_timeout() -> Result<(), Box<dyn std::error::Error>> {
+        let input_file = temp_input(json!({}))?;
+
+        Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/infinite_loop.wasm"])
+            .args(["--timeout", "100"])
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .assert()
+            .failure()
+            .stdout(contains("The Function exceeded the timeout of 100ms, at "))
+            .stderr(contains("The Function execution failed."));
+
+        Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/noop.wasm"])
+            .args(["--timeout", "60000"])
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .assert()
+            .success();
+
+        Ok(())
+    }
+
+    #[test]
+    fn compare_codec() -> Result<(), Box<dyn std::error::Error>> {
+        let input_file = temp_input(json!({"count": 0}))?;
+
+        // noop echoes its input, so its MessagePack run doesn't output JSON
+        Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/noop.wasm"])
+            .arg("--compare-codec")
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .assert()
+            .failure()
+            .stdout(contains("JSON instructions: "))
+            .stdout(contains(
+                "Outputs differ (JSON != MessagePack):\n  /: {\"count\":0} != ",
+            ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn messagepack_input_echo() -> Result<(), Box<dyn std::error::Error>> {
+        let input_file = temp_input(json!({"count": 0}))?;
+
+        let output = Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/noop.wasm"])
+            .args(["--codec", "json-to-messagepack", "--json", "--input"])
+            .arg(input_file.as_os_str())
+            .output()?;
+        let function_run_result: FunctionRunResult = serde_json::from_slice(&output.stdout)?;
+
+        assert_eq!(function_run_result.input, json!({"count": 0}));
+        assert_eq!(
+            function_run_result.input_codec,
+            Some(InputCodec::Messagepack)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn redact_messagepack_input() -> Result<(), Box<dyn std::error::Error>> {
+        let input_file = temp_input(json!({"customer": {"email": "secret@example.com"}}))?;
+
+        let output = Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/noop.wasm"])
+            .args(["--codec", "json-to-messagepack", "--json"])
+            .args(["--redact", "/customer/email", "--input"])
+            .arg(input_file.as_os_str())
+            .output()?;
+        let function_run_result: FunctionRunResult = serde_json::from_slice(&output.stdout)?;
+
+        assert!(!String::from_utf8(output.stdout)?.contains("secret@example.com"));
+        assert_eq!(
+            function_run_result.input,
+            json!({"customer": {"email": "[REDACTED]"}})
+        );
+
+        Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/noop.wasm"])
+            .args(["--codec", "json-to-messagepack", "--json"])
+            .args(["--redact", "/customer/phone", "--input"])
+            .arg(input_file.as_os_str())
+            .assert()
+            .failure()
+            .stdout("")
+            .stderr(contains("matches neither the input nor the output"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn expected_exports() -> Result<(), Box<dyn std::error::Error>> {
+        let input_file = temp_input(json!({}))?;
+
+        Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/export_all.wasm"])
+            .args(["--export", "a", "--expected-exports", "a,with_param,b"])
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .assert()
+            .success();
+
+        Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/export_all.wasm"])
+            .args(["--export", "a", "--expected-exports", "a"])
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .assert()
+            .failure()
+            .stderr("Error: The Function exports unexpected functions: `b`, `with_param`\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn max_instructions_per_input_byte() -> Result<(), Box<dyn std::error::Error>> {
+        let input_file = temp_input(json!({"count": 0}))?;
+
+        Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/noop.wasm"])
+            .args(["--max-instructions-per-input-byte", "1000000"])
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .assert()
+            .success()
+            .stdout(contains("Instructions per input byte: "));
+
+        Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/noop.wasm"])
+            .args(["--max-instructions-per-input-byte", "1"])
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .assert()
+            .failure()
+            .stderr(contains(
+                "instructions per input byte, more than the limit of 1\n",
+            ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn replay() -> Result<(), Box<dyn std::error::Error>> {
+        let input_file = temp_input(json!({"count": 7}))?;
+        let result_file = assert_fs::NamedTempFile::new("result.json")?;
+
+        let output = Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/noop.wasm"])
+            .arg("--json")
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .output()?;
+        result_file.write_binary(&output.stdout)?;
+
+        let output = Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/noop.wasm"])
+            .arg("--output-only")
+            .arg("--replay")
+            .arg(result_file.as_os_str())
+            .output()?;
+
+        assert!(output.status.success());
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&output.stdout)?,
+            json!({"count": 7})
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn replay_with_recorded_codec() -> Result<(), Box<dyn std::error::Error>> {
+        // {"count":5} as MessagePack, which isn't valid UTF-8
+        let input_base64 = "gaVjb3VudAU=";
+        let replayed_input = |codec: &str| -> Result<String, Box<dyn std::error::Error>> {
+            let result_file = assert_fs::NamedTempFile::new("result.json")?;
+            let output = Command::cargo_bin("function-runner")?
+                .args(["--function", "tests/fixtures/build/noop.wasm"])
+                .args(["--codec", codec, "--json", "--input-base64", input_base64])
+                .output()?;
+            result_file.write_binary(&output.stdout)?;
+
+            let output = Command::cargo_bin("function-runner")?
+                .args(["--function", "tests/fixtures/build/noop.wasm"])
+                .args(["--print-input-encoded", "hex", "--replay"])
+                .arg(result_file.as_os_str())
+                .output()?;
+            assert!(output.status.success());
+            Ok(String::from_utf8(output.stdout)?)
+        };
+
+        assert_eq!(replayed_input("raw")?, "81a5636f756e7405\n");
+        assert_eq!(replayed_input("messagepack")?, "81a5636f756e7405\n");
+
+        Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/noop.wasm"])
+            .args(["--replay", "result.json", "--codec", "raw"])
+            .assert()
+            .failure()
+            .stderr(contains("cannot be used with"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn input_jsonpath() -> Result<(), Box<dyn std::error::Error>> {
+        let input_file = temp_input(json!({"data": {"functionInput": {"count": 0}}}))?;
+
+        let output = Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/noop.wasm"])
+            .args(["--output-only", "--input-jsonpath", "$.data.functionInput"])
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .output()?;
+
+        assert!(output.status.success());
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&output.stdout)?,
+            json!({"count": 0})
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn input_jsonpath_no_match() -> Result<(), Box<dyn std::error::Error>> {
+        let input_file = temp_input(json!({"data": {"functionInput": {"count": 0}}}))?;
+
+        Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/noop.wasm"])
+            .args(["--input-jsonpath", "$.data.missing"])
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .assert()
+            .failure()
+            .stderr("Error: JSONPath `$.data.missing` matches no value in the input\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn input_jsonpath_multiple_matches() -> Result<(), Box<dyn std::error::Error>> {
+        let input_file = temp_input(json!({"requests": [{"count": 0}, {"count": 1}]}))?;
+
+        Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/noop.wasm"])
+            .args(["--input-jsonpath", "$.requests[*]"])
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .assert()
+            .failure()
+            .stderr(
+                "Error: JSONPath `$.requests[*]` matches 2 values in the input, expected one\n",
+            );
+
+        Ok(())
+    }
+
+    #[test]
+    fn measure_compile_only() -> Result<(), Box<dyn std::error::Error>> {
+        Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/noop.wasm"])
+            .args(["--measure-compile-only", "3"])
+            .assert()
+            .success()
+            .stdout(contains("Compile time over 3 iteration(s):\nmin: "))
+            .stdout(contains("\np99: "));
+
+        Ok(())
+    }
+
+    #[test]
+    fn codec_auto() -> Result<(), Box<dyn std::error::Error>> {
+        let detected_codec = |input_base64: &str| -> Result<_, Box<dyn std::error::Error>> {
+            let output = Command::cargo_bin("function-runner")?
+                .args(["--function", "tests/fixtures/build/noop.wasm"])
+                .args(["--codec", "auto", "--json", "--input-base64", input_base64])
+                .output()?;
+            let function_run_result: FunctionRunResult = serde_json::from_slice(&output.stdout)?;
+            Ok((function_run_result.input_codec, function_run_result.input))
+        };
+
+        // {"count":0} as JSON and as MessagePack, then bytes that are neither
+        assert_eq!(
+            detected_codec("eyJjb3VudCI6MH0=")?,
+            (Some(InputCodec::Json), json!({"count": 0}))
+        );
+        assert_eq!(
+            detected_codec("gaVjb3VudAA=")?,
+            (Some(InputCodec::Messagepack), json!({"count": 0}))
+        );
+        assert_eq!(
+            detected_codec("AAEC")?,
+            (Some(InputCodec::Raw), json!("AAEC"))
+        );
+        // A top-level MessagePack array, [1], is only detected as raw
+        assert_eq!(
+            detected_codec("kQE=")?,
+            (Some(InputCodec::Raw), json!("kQE="))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn output_assertions() -> Result<(), Box<dyn std::error::Error>> {
+        let input_file = temp_input(json!({"count": 0}))?;
+
+        Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/noop.wasm"])
+            .args(["--assert", "/count==0", "--assert", "/count"])
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .assert()
+            .success();
+
+        Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/noop.wasm"])
+            .args(["--assert", "/count!=0", "--assert", "/missing"])
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .assert()
+            .failure()
+            .stderr(contains("Assertion `/count!=0` failed: `/count` is 0\n"))
+            .stderr(contains(
+                "Assertion `/missing` failed: no value at `/missing`\n",
+            ))
+            .stderr(contains("Error: 2 output assertion(s) failed\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn max_instructions_soft_and_hard() -> Result<(), Box<dyn std::error::Error>> {
+        let input_file = temp_input(json!({"count": 0}))?;
+
+        Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/noop.wasm"])
+            .args(["--max-instructions-soft", "1000"])
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .assert()
+            .success()
+            .stdout(contains("Instructions Soft Limit: 1K"))
+            .stdout(contains(
+                "Warning: Instructions exceeded the soft limit of 1000",
+            ));
+
+        Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/noop.wasm"])
+            .args(["--max-instructions-hard", "1000"])
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .assert()
+            .failure()
+            .stdout(contains("Instructions Hard Limit: 1K"))
+            .stdout(contains(
+                "The Function exceeded the hard limit of 1000 instructions.",
+            ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn verify_determinism() -> Result<(), Box<dyn std::error::Error>> {
+        let input_file = temp_input(json!({"count": 0}))?;
+
+        let output = Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/noop.wasm", "--json"])
+            .arg("--verify-determinism")
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .output()?;
+
+        assert!(output.status.success());
+        let result = serde_json::from_slice::<FunctionRunResult>(&output.stdout)?;
+        assert_eq!(result.deterministic, Some(true));
+
+        Ok(())
+    }
+
+    #[test]
+    fn print_input_encoded() -> Result<(), Box<dyn std::error::Error>> {
+        let input_file = temp_input(json!({"a": 1}))?;
+
+        Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/noop.wasm"])
+            .args(["--codec", "json-to-messagepack"])
+            .args(["--print-input-encoded", "hex"])
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .assert()
+            .success()
+            .stdout("81a16101\n");
+
+        Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/noop.wasm"])
+            .args(["--print-input-encoded", "base64"])
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .assert()
+            .success()
+            .stdout("eyJhIjoxfQ==\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_payload() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("function-runner")?;
+        let payload_file = temp_input(json!({"input": {"count": 0}, "configuration": {}}))?;
+
+        cmd.args(["--function", "tests/fixtures/build/noop.wasm"])
+            .arg("--payload")
+            .arg(payload_file.as_os_str());
+        cmd.assert().success().stdout(contains("configuration"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn run_input_base64() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("function-runner")?;
+
+        // {"count":0}
+        cmd.args(["--function", "tests/fixtures/build/noop.wasm"])
+            .args(["--input-base64", "eyJjb3VudCI6MH0="]);
+        cmd.assert().success().stdout(contains("\"count\": 0"));
 
         Ok(())
     }
 
     #[test]
-    #[ignore = "This test hangs on CI but runs locally, is_terminal is likely returning false in CI"]
-    fn run_function_no_input() -> Result<(), Box<dyn std::error::Error>> {
+    fn invalid_input_base64() -> Result<(), Box<dyn std::error::Error>> {
         let mut cmd = Command::cargo_bin("function-runner")?;
 
-        cmd.args(["--function", "tests/fixtures/build/exit_code.wasm"]);
+        cmd.args(["--function", "tests/fixtures/build/noop.wasm"])
+            .args(["--input-base64", "not base64!"]);
         cmd.assert()
             .failure()
-            .stderr("Error: You must provide input via the --input flag or piped via stdin.\n");
+            .stderr(contains("Error: Invalid base64 input: "));
 
         Ok(())
     }
 
     #[test]
-    fn run_json() -> Result<(), Box<dyn std::error::Error>> {
+    fn payload_missing_configuration() -> Result<(), Box<dyn std::error::Error>> {
         let mut cmd = Command::cargo_bin("function-runner")?;
-        let input_file = temp_input(json!({"count": 0}))?;
+        let payload_file = temp_input(json!({"input": {"count": 0}}))?;
 
         cmd.args(["--function", "tests/fixtures/build/noop.wasm"])
-            .arg("--json")
-            .arg("--input")
-            .arg(input_file.as_os_str());
-        cmd.assert().success();
-        let output = cmd.output().expect("Wasn't able to get output");
-        let _ = serde_json::from_slice::<FunctionRunResult>(&output.stdout)
-            .expect("This shouldn't fail");
+            .arg("--payload")
+            .arg(payload_file.as_os_str());
+        cmd.assert()
+            .failure()
+            .stderr("Error: Invalid payload: missing `configuration` key\n");
 
         Ok(())
     }
@@ -124,7 +865,7 @@ mod tests {
             .arg(input_file.as_os_str());
         cmd.assert()
             .failure()
-            .stderr("Error: Couldn\'t load the Function \"test/file/doesnt/exist\": failed to read from `test/file/doesnt/exist`\n");
+            .stderr("Error: Couldn\'t load the Function \"test/file/doesnt/exist\": failed to read from `test/file/doesnt/exist`: No such file or directory (os error 2)\n");
 
         Ok(())
     }
@@ -160,6 +901,24 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn profile_top_prints_hottest_stacks() -> Result<(), Box<dyn std::error::Error>> {
+        let temp = assert_fs::TempDir::new()?;
+        let mut cmd = Command::cargo_bin("function-runner")?;
+
+        cmd.current_dir(temp.path())
+            .arg("--function")
+            .arg(std::env::current_dir()?.join("tests/fixtures/build/js_function_v3.wasm"))
+            .arg("--input")
+            .arg(std::env::current_dir()?.join("tests/fixtures/input/js_function_input.json"))
+            .args(["--profile-top", "3"]);
+        cmd.assert().success().stdout(contains("Hottest stacks:\n"));
+        temp.child("js_function_v3.perf")
+            .assert(predicate::path::exists());
+
+        Ok(())
+    }
+
     #[test]
     fn profile_frequency_triggers_profiling() -> Result<(), Box<dyn std::error::Error>> {
         let (mut cmd, temp) = profile_base_cmd_in_temp_dir()?;
@@ -171,6 +930,218 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn benchmark_json_appends_entries() -> Result<(), Box<dyn std::error::Error>> {
+        let input_file = temp_input(json!({"count": 0}))?;
+        let history = assert_fs::NamedTempFile::new("history.jsonl")?;
+
+        for _ in 0..2 {
+            let mut cmd = Command::cargo_bin("function-runner")?;
+            cmd.args(["--function", "tests/fixtures/build/noop.wasm"])
+                .arg("--input")
+                .arg(input_file.as_os_str())
+                .arg("--benchmark-json")
+                .arg(history.path())
+                .args(["--label", "abc123"])
+                .assert()
+                .success();
+        }
+
+        let contents = std::fs::read_to_string(history.path())?;
+        let entries = contents
+            .lines()
+            .map(serde_json::from_str::<serde_json::Value>)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        assert_eq!(entries.len(), 2);
+        for entry in entries {
+            assert_eq!(entry["commit"], "abc123");
+            assert!(entry["instructions"].as_u64().unwrap() > 0);
+            assert!(entry["memory_usage"].is_u64());
+            assert!(entry["runtime_us"].is_u64());
+            assert!(entry["compile_us"].is_u64());
+            assert!(entry["timestamp"].is_u64());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn output_hash_ignores_key_order() -> Result<(), Box<dyn std::error::Error>> {
+        let hash_for = |input: &str| -> Result<String, Box<dyn std::error::Error>> {
+            let input_file = assert_fs::NamedTempFile::new("input.json")?;
+            input_file.write_str(input)?;
+
+            let output = Command::cargo_bin("function-runner")?
+                .args(["--function", "tests/fixtures/build/noop.wasm"])
+                .args(["--json", "--output-hash"])
+                .arg("--input")
+                .arg(input_file.as_os_str())
+                .output()?;
+            let result = serde_json::from_slice::<FunctionRunResult>(&output.stdout)?;
+
+            Ok(result.output_hash.expect("output_hash should be present"))
+        };
+
+        let hash = hash_for(r#"{"a": 1, "b": 2}"#)?;
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, hash_for(r#"{"b": 2, "a": 1}"#)?);
+        assert_ne!(hash, hash_for(r#"{"a": 1, "b": 3}"#)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn compare_baseline() -> Result<(), Box<dyn std::error::Error>> {
+        let input_file = temp_input(json!({"count": 0}))?;
+        let baseline = assert_fs::NamedTempFile::new("baseline.json")?;
+
+        let output = Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/noop.wasm"])
+            .arg("--json")
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .output()?;
+        baseline.write_binary(&output.stdout)?;
+
+        let mut cmd = Command::cargo_bin("function-runner")?;
+        cmd.args(["--function", "tests/fixtures/build/noop.wasm"])
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .arg("--compare-baseline")
+            .arg(baseline.path());
+
+        cmd.assert()
+            .success()
+            .stdout(contains("Compared to baseline:"))
+            .stdout(contains("(+0.00%)"))
+            .stdout(contains("REGRESSION").not());
+
+        Ok(())
+    }
+
+    #[test]
+    fn zstd_input() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("function-runner")?;
+        let input_file = assert_fs::NamedTempFile::new("input.json.zst")?;
+        input_file.write_binary(&zstd::encode_all(
+            json!({"count": 42}).to_string().as_bytes(),
+            0,
+        )?)?;
+
+        cmd.args(["--function", "tests/fixtures/build/noop.wasm"])
+            .arg("--input")
+            .arg(input_file.as_os_str());
+        cmd.assert().success().stdout(contains("\"count\": 42"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn profile_writes_zstd_file() -> Result<(), Box<dyn std::error::Error>> {
+        let (mut cmd, temp) = profile_base_cmd_in_temp_dir()?;
+        cmd.args(["--profile-out", "foo.perf.zst"])
+            .assert()
+            .success();
+
+        let compressed = std::fs::read(temp.child("foo.perf.zst").path())?;
+        String::from_utf8(zstd::decode_all(compressed.as_slice())?)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn forbid_wasi() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("function-runner")?;
+        let input_file = temp_input(json!({"code": 0}))?;
+
+        cmd.args(["--function", "tests/fixtures/build/exit_code.wasm"])
+            .arg("--forbid-wasi")
+            .arg("--input")
+            .arg(input_file.as_os_str());
+        cmd.assert().failure().stderr(contains(
+            "The Function imports WASI (`wasi_snapshot_preview1::",
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn forbid_wasi_without_wasi_imports() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("function-runner")?;
+        let input_file = temp_input(json!({}))?;
+
+        cmd.args(["--function", "tests/fixtures/build/no_output.wasm"])
+            .arg("--forbid-wasi")
+            .arg("--input")
+            .arg(input_file.as_os_str());
+        cmd.assert().success();
+
+        Ok(())
+    }
+
+    #[test]
+    fn trace_imports() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("function-runner")?;
+
+        cmd.args(["--function", "tests/fixtures/build/js_function_v3.wasm"])
+            .arg("--trace-imports")
+            .args(["--input", "tests/fixtures/input/js_function_input.json"]);
+        cmd.assert()
+            .success()
+            .stderr(contains("javy_quickjs_provider_v3."))
+            .stderr(contains("wasi_snapshot_preview1.").not());
+
+        Ok(())
+    }
+
+    #[test]
+    fn input_transform() -> Result<(), Box<dyn std::error::Error>> {
+        let input_file = temp_input(json!({"cart": {"note": "hi", "lines": []}}))?;
+
+        let output = Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/noop.wasm", "--json"])
+            .args(["--input-transform", "remove /cart/note"])
+            .args(["--input-transform", "rename /cart/lines /cart/items"])
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .output()?;
+        let result = serde_json::from_slice::<FunctionRunResult>(&output.stdout)?;
+
+        assert_eq!(result.input, json!({"cart": {"items": []}}));
+
+        Command::cargo_bin("function-runner")?
+            .args(["--function", "tests/fixtures/build/noop.wasm"])
+            .args(["--input-transform", "remove /missing"])
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .assert()
+            .failure()
+            .stderr(contains(
+                "Couldn't apply `remove /missing`: Input transform failed: no value at `/missing`",
+            ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn dump_on_trap_writes_memory() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("function-runner")?;
+        let input_file = temp_input(json!({}))?;
+        let dump = assert_fs::NamedTempFile::new("memory.bin")?;
+
+        cmd.args(["--function", "tests/fixtures/build/trap.wasm"])
+            .arg("--dump-on-trap")
+            .arg(dump.path())
+            .arg("--input")
+            .arg(input_file.as_os_str());
+        cmd.assert().failure();
+
+        assert!(std::fs::read(dump.path())?.starts_with(b"trap"));
+
+        Ok(())
+    }
+
     #[test]
     fn incorrect_input() -> Result<(), Box<dyn std::error::Error>> {
         let mut cmd = Command::cargo_bin("function-runner")?;
@@ -193,6 +1164,31 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn incorrect_input_json() -> Result<(), Box<dyn std::error::Error>> {
+        let input_file = temp_input(json!({}))?;
+
+        let output = Command::cargo_bin("function-runner")?
+            .args([
+                "--function",
+                "tests/fixtures/build/exit_code.wasm",
+                "--json",
+            ])
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .output()?;
+
+        assert!(!output.status.success());
+        let result = serde_json::from_slice::<serde_json::Value>(&output.stdout)
+            .expect("--json should print a JSON object even when the output is invalid");
+        assert_eq!(result["success"], json!(false));
+        assert!(result["instructions"].is_u64());
+        assert!(result["output"]["error"].is_string());
+        assert!(result["output"]["stdout"].is_string());
+
+        Ok(())
+    }
+
     #[test]
     fn exports() -> Result<(), Box<dyn std::error::Error>> {
         let mut cmd = Command::cargo_bin("function-runner")?;
@@ -207,6 +1203,36 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn export_index() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("function-runner")?;
+        let input_file = temp_input(json!({}))?;
+        cmd.args(["--function", "tests/fixtures/build/exports.wasm"])
+            .args(["--export-index", "1"])
+            .arg("--input")
+            .arg(input_file.as_os_str());
+
+        cmd.assert().success().stdout(contains("export1"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn export_index_not_a_function() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("function-runner")?;
+        let input_file = temp_input(json!({}))?;
+        cmd.args(["--function", "tests/fixtures/build/exports.wasm"])
+            .args(["--export-index", "0"])
+            .arg("--input")
+            .arg(input_file.as_os_str());
+
+        cmd.assert().failure().stderr(contains(
+            "Export index 0 (`memory`) is not a function export",
+        ));
+
+        Ok(())
+    }
+
     #[test]
     fn missing_export() -> Result<(), Box<dyn std::error::Error>> {
         let mut cmd = Command::cargo_bin("function-runner")?;
@@ -351,4 +1377,107 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_scale_limits_analyzer_with_graphql_document() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let mut cmd = Command::cargo_bin("function-runner")?;
+
+        let input_data = vec![json!({"quantity": 2}); 400];
+        let json_data = json!({
+            "cart": {
+                "lines": input_data
+            }
+        });
+        let input_file = temp_input(json_data)?;
+        let document = assert_fs::NamedTempFile::new("document.graphql")?;
+        document.write_str(&format!(
+            "{}\n{}",
+            std::fs::read_to_string("tests/fixtures/query/query.graphql")?,
+            std::fs::read_to_string("tests/fixtures/schema/schema.graphql")?
+        ))?;
+
+        cmd.args(["--function", "tests/fixtures/build/noop.wasm"])
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .arg("--graphql-document")
+            .arg(document.path());
+
+        cmd.assert()
+            .success()
+            .stdout(contains("Input Size: 250.00KB"))
+            .stdout(contains("Instructions: 22M"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scale_limits_analyzer_reports_clamping() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("function-runner")?;
+
+        let input_data = vec![json!({"quantity": 2}); 4000];
+        let json_data = json!({
+            "cart": {
+                "lines": input_data
+            }
+        });
+        let input_file = temp_input(json_data)?;
+
+        cmd.args(["--function", "tests/fixtures/build/noop.wasm"])
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .arg("--schema-path")
+            .arg("tests/fixtures/schema/schema.graphql")
+            .arg("--query-path")
+            .arg("tests/fixtures/query/query.graphql");
+
+        cmd.assert()
+            .success()
+            .stderr(contains("Scale factor clamped from 20.0 to 10.0"))
+            .stdout(contains("Instructions: 110M"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scale_limits_analyzer_with_assumed_full_query() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let mut cmd = Command::cargo_bin("function-runner")?;
+
+        let input_data = vec![json!({"quantity": 2}); 400];
+        let json_data = json!({
+            "cart": {
+                "lines": input_data
+            }
+        });
+        let input_file = temp_input(json_data)?;
+
+        cmd.args(["--function", "tests/fixtures/build/noop.wasm"])
+            .arg("--input")
+            .arg(input_file.as_os_str())
+            .arg("--schema-path")
+            .arg("tests/fixtures/schema/schema.graphql")
+            .arg("--assume-full-query");
+
+        cmd.assert()
+            .success()
+            .stdout(contains("Input Size: 250.00KB"))
+            .stdout(contains("Output Size: 39.06KB"))
+            .stdout(contains("Instructions: 22M"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn show_selected_limits() -> Result<(), Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("function-runner")?;
+
+        cmd.args(["--schema-path", "tests/fixtures/schema/schema.graphql"])
+            .args(["--query-path", "tests/fixtures/query/query.graphql"])
+            .arg("--show-selected-limits");
+
+        cmd.assert().success().stdout("cart.lines (rate: 0.005)\n");
+
+        Ok(())
+    }
 }